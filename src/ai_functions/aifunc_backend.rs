@@ -1,41 +1,109 @@
 use ai_functions::ai_function;
 
+#[ai_function]
+pub fn print_backend_file_plan(_project_description_and_template: &str) {
+    /// INPUT: Takes in a PROJECT_DESCRIPTION and CODE_TEMPLATE, and optionally an API_CONTRACT, for a website backend build
+    /// FUNCTION: Plans how the backend webserver code should be split into Rust source files
+    /// IMPORTANT: Use a small number of modules, for example: src/main.rs, src/models.rs, src/db.rs, src/handlers.rs, src/routes.rs
+    /// IMPORTANT: Every path must start with src/ and end with .rs. src/main.rs must always be included.
+    /// OUTPUT: Prints a JSON list of files in the following format. No commentary or anything else:
+    /// [{"path": "src/main.rs", "purpose": "starts the actix-web server"}, {"path": "src/models.rs", "purpose": "..."}, ...]
+    println!(OUTPUT)
+}
+
 #[ai_function]
 pub fn print_backend_webserver_code(_project_description_and_template: &str) {
-    /// INPUT: Takes in a PROJECT_DESCRIPTION and CODE_TEMPLATE for a website backend build
+    /// INPUT: Takes in a PROJECT_DESCRIPTION, CODE_TEMPLATE and FILE_PLAN, and optionally an API_CONTRACT, for a website backend build
     /// IMPORTANT: The backend code is ONLY an example. If the Project Description requires it, make as many changes as you like.
     /// IMPORTANT: You do not need to follow the backend code exactly. Write functions that make sense for the users request if required.
     /// FUNCTION: Takes an existing set of code marked as CODE_TEMPLATE and updates or re-writes it to work for the purpose in the PROJECT_DESCRIPTION
+    /// IMPORTANT: Splits the code into the files listed in FILE_PLAN and declares the modules with `mod` in src/main.rs
+    /// IMPORTANT: If an API_CONTRACT is given, implement exactly the routes and methods it lists, with its request and response bodies, and no other routes
+    /// IMPORTANT: The server must bind to 127.0.0.1 on the port in the PORT environment variable, defaulting to 8080, like the CODE_TEMPLATE does
     /// IMPORTANT: The following libraries are already installed
     ///   reqwest, serde, serde_json, tokio, actix-web, async-trait, actix_cors
     /// No other external libraries should be used. Write functions that fit with the description from the PROJECT_DESCRIPTION
-    /// OUTPUT: Print ONLY the code, nothing else. This function ONLY prints code.
+    /// OUTPUT: Print ONLY the files, nothing else. Every file is printed in the following format:
+    /// === FILE: src/main.rs ===
+    /// ...code...
+    /// === END FILE ===
     println!(OUTPUT)
 }
 
 #[ai_function]
 pub fn print_improved_webserver_code(_project_description_and_template: &str) {
-    /// INPUT: Takes in a PROJECT_DESCRIPTION and CODE_TEMPLATE for a website backend build
+    /// INPUT: Takes in a PROJECT_DESCRIPTION and CODE_TEMPLATE, and optionally an API_CONTRACT and LINT_ERRORS, for a website backend build
     /// FUNCTION: Performs the following tasks:
     ///   1. Removes any bugs in the code and adds minor additional functionality
     ///   2. Makes sure everything requested in the spec from a backend standpoint was followed. If not, add the feature. No code should be implemented later. Everything should be written now.
     ///   3. ONLY writes the code. No commentary.
+    /// IMPORTANT: If an API_CONTRACT is given, implement exactly the routes and methods it lists, with its request and response bodies, and no other routes
+    /// IMPORTANT: If LINT_ERRORS are given, fix every one of them. They are clippy lints shown with the numbered lines around them (the linted line is marked with >)
+    /// IMPORTANT: The server must bind to 127.0.0.1 on the port in the PORT environment variable, defaulting to 8080, like the CODE_TEMPLATE does
     /// IMPORTANT: The following libraries are already installed. Does not use ANY libraries other than what was provided in the template
-    ///   reqwest, serde, serde_json, tokio, actix-web, async-trait
+    ///   reqwest, serde, serde_json, tokio, actix-web, async-trait, actix_cors
+    /// OUTPUT: Prints every file of the project, including unchanged ones, in the following format:
+    /// === FILE: src/main.rs ===
+    /// ...code...
+    /// === END FILE ===
     println!(OUTPUT)
 }
 
 #[ai_function]
 pub fn print_fixed_code(_broken_code_with_bugs: &str) {
-    /// INPUT: Takes in Rust BROKEN_CODE and the ERROR_BUGS found
+    /// INPUT: Takes in Rust BROKEN_CODE files, the names of the OTHER_FILES in the project, the ERROR_BUGS found and optionally FIX_HINTS that usually fix errors like these. ERROR_BUGS may be compiler errors shown with the numbered lines around them (the erroring line is marked with >), endpoints that failed their checks or server panics with the server stderr
     /// FUNCTION: Removes bugs from code
     /// IMPORTANT: Only prints out the new and improved code. No commentary or anything else
+    /// OUTPUT: Prints only the files that were changed, in the following format:
+    /// === FILE: src/handlers.rs ===
+    /// ...code...
+    /// === END FILE ===
+    println!(OUTPUT)
+}
+
+#[ai_function]
+pub fn print_improved_webserver_edits(_project_description_and_template: &str) {
+    /// INPUT: Takes in a PROJECT_DESCRIPTION and CODE_TEMPLATE, and optionally an API_CONTRACT and LINT_ERRORS, for a website backend build
+    /// FUNCTION: Performs the same tasks as improving the webserver code, but only prints the edits needed:
+    ///   1. Removes any bugs in the code and adds minor additional functionality
+    ///   2. Makes sure everything requested in the spec from a backend standpoint was followed. If not, add the feature.
+    /// IMPORTANT: If an API_CONTRACT is given, implement exactly the routes and methods it lists, with its request and response bodies, and no other routes
+    /// IMPORTANT: If LINT_ERRORS are given, fix every one of them. They are clippy lints shown with the numbered lines around them (the linted line is marked with >)
+    /// IMPORTANT: The server must bind to 127.0.0.1 on the port in the PORT environment variable, defaulting to 8080, like the CODE_TEMPLATE does
+    /// IMPORTANT: The following libraries are already installed. Does not use ANY libraries other than what was provided in the template
+    ///   reqwest, serde, serde_json, tokio, actix-web, async-trait, actix_cors
+    /// IMPORTANT: The SEARCH text must be copied exactly from the current code and must only match once in its file.
+    /// To create a new file leave the SEARCH text empty.
+    /// OUTPUT: Prints ONLY edit blocks, no commentary, in the following format:
+    /// === EDIT: src/main.rs ===
+    /// <<<<<<< SEARCH
+    /// ...existing lines...
+    /// =======
+    /// ...replacement lines...
+    /// >>>>>>> REPLACE
+    /// === END EDIT ===
+    println!(OUTPUT)
+}
+
+#[ai_function]
+pub fn print_fixed_code_edits(_broken_code_with_bugs: &str) {
+    /// INPUT: Takes in Rust BROKEN_CODE files, the names of the OTHER_FILES in the project, the ERROR_BUGS found and optionally FIX_HINTS that usually fix errors like these. ERROR_BUGS may be compiler errors shown with the numbered lines around them (the erroring line is marked with >), endpoints that failed their checks or server panics with the server stderr
+    /// FUNCTION: Removes bugs from code by printing the smallest edits that fix the ERROR_BUGS
+    /// IMPORTANT: The SEARCH text must be copied exactly from the BROKEN_CODE and must only match once in its file.
+    /// OUTPUT: Prints ONLY edit blocks, no commentary, in the following format:
+    /// === EDIT: src/handlers.rs ===
+    /// <<<<<<< SEARCH
+    /// ...existing lines...
+    /// =======
+    /// ...replacement lines...
+    /// >>>>>>> REPLACE
+    /// === END EDIT ===
     println!(OUTPUT)
 }
 
 #[ai_function]
 pub fn print_rest_api_endpoints(_code_input: &str) {
-    /// INPUT: Takes in Rust webserver CODE_INPUT based on actix-web
+    /// INPUT: Takes in Rust webserver CODE_INPUT based on actix-web. The code may be split into several files marked with === FILE: path ===
     /// FUNCTION: Prints out the JSON schema for url endpoints and their respective types
     /// LOGIC: Script analyses all code and can categorize into the following object keys:
     ///   "route": This represents the url path of the endpoint
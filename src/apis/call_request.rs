@@ -1,4 +1,7 @@
-use crate::models::general::llm::{APIResponse, ChatCompletion, Message};
+use crate::models::general::{
+    error::AutoGptError,
+    llm::{APIResponse, ChatCompletion, Message},
+};
 use dotenv::dotenv;
 use reqwest::{
     header::{HeaderMap, HeaderValue},
@@ -6,11 +9,19 @@ use reqwest::{
 };
 use std::env;
 
-pub async fn call_gpt(messages: Vec<Message>) -> Result<String, Box<dyn std::error::Error + Send>> {
+pub const DEFAULT_MODEL: &str = "gpt-4";
+
+pub async fn call_gpt(
+    client: &Client,
+    messages: Vec<Message>,
+    model: &str,
+) -> Result<String, AutoGptError> {
     dotenv().ok();
 
-    let api_key: String = env::var("OPEN_AI_KEY").expect("OPEN_AI_KEY not found in env");
-    let api_org: String = env::var("OPEN_AI_ORG").expect("OPEN_AI_ORG not found in env");
+    let api_key: String = env::var("OPEN_AI_KEY")
+        .map_err(|_| AutoGptError::Config("OPEN_AI_KEY not found in env".to_string()))?;
+    let api_org: String = env::var("OPEN_AI_ORG")
+        .map_err(|_| AutoGptError::Config("OPEN_AI_ORG not found in env".to_string()))?;
 
     let url: &str = "https://api.openai.com/v1/chat/completions";
 
@@ -19,22 +30,17 @@ pub async fn call_gpt(messages: Vec<Message>) -> Result<String, Box<dyn std::err
     headers.insert(
         "authorization",
         HeaderValue::from_str(&format!("Bearer {}", api_key))
-            .map_err(|e| -> Box<dyn std::error::Error + Send> { Box::new(e) })?,
+            .map_err(|e| AutoGptError::Config(format!("invalid OPEN_AI_KEY: {}", e)))?,
     );
 
     headers.insert(
         "OpenAI-Organization",
         HeaderValue::from_str(api_org.as_str())
-            .map_err(|e| -> Box<dyn std::error::Error + Send> { Box::new(e) })?,
+            .map_err(|e| AutoGptError::Config(format!("invalid OPEN_AI_ORG: {}", e)))?,
     );
 
-    let client: Client = Client::builder()
-        .default_headers(headers)
-        .build()
-        .map_err(|e| -> Box<dyn std::error::Error + Send> { Box::new(e) })?;
-
     let chat_completion: ChatCompletion = ChatCompletion {
-        model: "gpt-4".to_string(),
+        model: model.to_string(),
         messages,
         temperature: 0.1,
     };
@@ -50,15 +56,19 @@ pub async fn call_gpt(messages: Vec<Message>) -> Result<String, Box<dyn std::err
 
     let res: APIResponse = client
         .post(url)
+        .headers(headers)
         .json(&chat_completion)
         .send()
         .await
-        .map_err(|e| -> Box<dyn std::error::Error + Send> { Box::new(e) })?
+        .map_err(|e| AutoGptError::Llm(e.to_string()))?
         .json()
         .await
-        .map_err(|e| -> Box<dyn std::error::Error + Send> { Box::new(e) })?;
+        .map_err(|e| AutoGptError::Llm(e.to_string()))?;
 
-    Ok(res.choices[0].message.content.clone())
+    res.choices
+        .first()
+        .map(|choice| choice.message.content.clone())
+        .ok_or_else(|| AutoGptError::Llm("response has no choices".to_string()))
 }
 
 #[cfg(test)]
@@ -71,15 +81,12 @@ mod test {
             role: "user".to_string(),
             content: "Hello, how are you?. Give me a short response.".to_string(),
         }];
-        let res = call_gpt(messages).await;
+        let res = call_gpt(&Client::new(), messages, DEFAULT_MODEL).await;
         match res {
             Ok(res_str) => {
                 dbg!(res_str);
-                assert!(true);
-            }
-            Err(_) => {
-                assert!(false)
             }
+            Err(_) => panic!("Failed to call GPT"),
         }
     }
 }
@@ -0,0 +1,29 @@
+use super::call_request::call_gpt;
+use crate::models::general::{error::AutoGptError, llm::Message};
+use async_trait::async_trait;
+use reqwest::Client;
+use std::fmt::Debug;
+
+// Where agents send their prompts. Tests script the replies instead of calling a model.
+#[async_trait]
+pub trait LlmProvider: Debug + Send + Sync {
+    async fn chat(&self, messages: Vec<Message>, model: &str) -> Result<String, AutoGptError>;
+}
+
+#[derive(Debug, Clone)]
+pub struct OpenAiProvider {
+    client: Client,
+}
+
+impl OpenAiProvider {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiProvider {
+    async fn chat(&self, messages: Vec<Message>, model: &str) -> Result<String, AutoGptError> {
+        call_gpt(&self.client, messages, model).await
+    }
+}
@@ -1 +1,2 @@
 pub mod call_request;
+pub mod llm_provider;
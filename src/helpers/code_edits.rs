@@ -0,0 +1,289 @@
+use std::{env, fmt};
+
+use crate::models::general::project_files::{ManifestError, ProjectFiles};
+
+const EDIT_START: &str = "=== EDIT: ";
+const EDIT_MARKER_END: &str = " ===";
+const EDIT_END: &str = "=== END EDIT ===";
+const SEARCH_START: &str = "<<<<<<< SEARCH";
+const SEARCH_DIVIDER: &str = "=======";
+const REPLACE_END: &str = ">>>>>>> REPLACE";
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum EditMode {
+    Rewrite,
+    #[default]
+    Patch,
+}
+
+impl EditMode {
+    pub fn from_env() -> Self {
+        match env::var("BACKEND_EDIT_MODE").as_deref() {
+            Ok("rewrite") => Self::Rewrite,
+            _ => Self::Patch,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchReplace {
+    pub path: String,
+    pub search: String,
+    pub replace: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum EditError {
+    NoEdits,
+    Malformed(String),
+    InvalidFile(ManifestError),
+    UnknownFile(String),
+    SearchNotFound {
+        path: String,
+        search: String,
+    },
+    SearchAmbiguous {
+        path: String,
+        search: String,
+        matches: usize,
+    },
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoEdits => write!(f, "response contains no edits"),
+            Self::Malformed(line) => write!(f, "malformed edit block near '{}'", line),
+            Self::InvalidFile(e) => write!(f, "{}", e),
+            Self::UnknownFile(path) => write!(f, "edit targets unknown file '{}'", path),
+            Self::SearchNotFound { path, search } => {
+                write!(f, "search text not found in '{}':\n{}", path, search)
+            }
+            Self::SearchAmbiguous {
+                path,
+                search,
+                matches,
+            } => write!(
+                f,
+                "search text matches {} times in '{}':\n{}",
+                matches, path, search
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EditError {}
+
+enum ParseState {
+    Outside,
+    InEdit,
+    Search(Vec<String>),
+    Replace(Vec<String>, Vec<String>),
+}
+
+// Parses search/replace blocks in the format the *_edits AI functions print:
+//   === EDIT: src/handlers.rs ===
+//   <<<<<<< SEARCH
+//   ...existing lines...
+//   =======
+//   ...new lines...
+//   >>>>>>> REPLACE
+//   === END EDIT ===
+pub fn parse_edits(response: &str) -> Result<Vec<SearchReplace>, EditError> {
+    let mut edits: Vec<SearchReplace> = vec![];
+    let mut path: String = String::new();
+    let mut state: ParseState = ParseState::Outside;
+
+    for line in response.lines() {
+        let trimmed: &str = line.trim();
+        state = match state {
+            ParseState::Outside => {
+                match trimmed
+                    .strip_prefix(EDIT_START)
+                    .and_then(|rest| rest.strip_suffix(EDIT_MARKER_END))
+                {
+                    Some(edit_path) => {
+                        path = edit_path.trim().to_string();
+                        ParseState::InEdit
+                    }
+                    None => ParseState::Outside,
+                }
+            }
+            ParseState::InEdit if trimmed == SEARCH_START => ParseState::Search(vec![]),
+            ParseState::InEdit if trimmed == EDIT_END => ParseState::Outside,
+            ParseState::InEdit if trimmed.is_empty() || trimmed.starts_with("```") => {
+                ParseState::InEdit
+            }
+            ParseState::InEdit => return Err(EditError::Malformed(line.to_string())),
+            ParseState::Search(search) if trimmed == SEARCH_DIVIDER => {
+                ParseState::Replace(search, vec![])
+            }
+            ParseState::Search(mut search) => {
+                search.push(line.to_string());
+                ParseState::Search(search)
+            }
+            ParseState::Replace(search, replace) if trimmed == REPLACE_END => {
+                edits.push(SearchReplace {
+                    path: path.clone(),
+                    search: search.join("\n"),
+                    replace: replace.join("\n"),
+                });
+                ParseState::InEdit
+            }
+            ParseState::Replace(search, mut replace) => {
+                replace.push(line.to_string());
+                ParseState::Replace(search, replace)
+            }
+        };
+    }
+
+    match state {
+        ParseState::Outside if !edits.is_empty() => Ok(edits),
+        ParseState::Outside => Err(EditError::NoEdits),
+        _ => Err(EditError::Malformed(format!(
+            "unterminated edit for {}",
+            path
+        ))),
+    }
+}
+
+// Applies every edit or none of them. Returns only the files that changed.
+pub fn apply_edits(
+    files: &ProjectFiles,
+    edits: &[SearchReplace],
+) -> Result<ProjectFiles, EditError> {
+    let mut changed_files: ProjectFiles = ProjectFiles::default();
+
+    for edit in edits {
+        let current: Option<String> = changed_files
+            .get(&edit.path)
+            .or_else(|| files.get(&edit.path))
+            .cloned();
+
+        let updated: String = match current {
+            // An empty search block on a new path creates the file.
+            None if edit.search.trim().is_empty() => format!("{}\n", edit.replace),
+            None => return Err(EditError::UnknownFile(edit.path.clone())),
+            Some(contents) => {
+                let matches: usize = contents.matches(edit.search.as_str()).count();
+                match matches {
+                    1 => contents.replacen(edit.search.as_str(), &edit.replace, 1),
+                    0 => {
+                        return Err(EditError::SearchNotFound {
+                            path: edit.path.clone(),
+                            search: edit.search.clone(),
+                        })
+                    }
+                    _ => {
+                        return Err(EditError::SearchAmbiguous {
+                            path: edit.path.clone(),
+                            search: edit.search.clone(),
+                            matches,
+                        })
+                    }
+                }
+            }
+        };
+
+        changed_files
+            .insert(&edit.path, updated)
+            .map_err(EditError::InvalidFile)?;
+    }
+
+    Ok(changed_files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::general::project_files::MAIN_FILE;
+
+    fn project() -> ProjectFiles {
+        let mut files: ProjectFiles = ProjectFiles::default();
+        files
+            .insert(
+                MAIN_FILE,
+                "mod handlers;\n\nfn main() {\n    println!(\"hi\");\n}\n".to_string(),
+            )
+            .unwrap();
+        files
+            .insert(
+                "src/handlers.rs",
+                "pub fn a() {}\npub fn b() {}\npub fn b() {}\n".to_string(),
+            )
+            .unwrap();
+        files
+    }
+
+    #[test]
+    fn tests_edits_are_parsed_and_applied() {
+        let response: &str = "Sure:\n\
+            === EDIT: src/main.rs ===\n\
+            <<<<<<< SEARCH\n    println!(\"hi\");\n=======\n    println!(\"hello\");\n    run();\n>>>>>>> REPLACE\n\
+            === END EDIT ===\n\
+            === EDIT: src/db.rs ===\n\
+            <<<<<<< SEARCH\n=======\npub fn db() {}\n>>>>>>> REPLACE\n\
+            === END EDIT ===\n";
+
+        let edits: Vec<SearchReplace> = parse_edits(response).unwrap();
+        assert_eq!(edits.len(), 2);
+        assert_eq!(edits[0].search, "    println!(\"hi\");");
+
+        let changed: ProjectFiles = apply_edits(&project(), &edits).unwrap();
+        assert_eq!(
+            changed.paths(),
+            vec!["src/db.rs".to_string(), MAIN_FILE.to_string()]
+        );
+        assert_eq!(
+            changed.get(MAIN_FILE).unwrap(),
+            "mod handlers;\n\nfn main() {\n    println!(\"hello\");\n    run();\n}\n"
+        );
+        assert_eq!(changed.get("src/db.rs").unwrap(), "pub fn db() {}\n");
+    }
+
+    #[test]
+    fn tests_conflicting_edits_are_rejected() {
+        let missing: SearchReplace = SearchReplace {
+            path: MAIN_FILE.to_string(),
+            search: "fn missing() {}".to_string(),
+            replace: "".to_string(),
+        };
+        assert!(matches!(
+            apply_edits(&project(), &[missing]),
+            Err(EditError::SearchNotFound { .. })
+        ));
+
+        let ambiguous: SearchReplace = SearchReplace {
+            path: "src/handlers.rs".to_string(),
+            search: "pub fn b() {}".to_string(),
+            replace: "".to_string(),
+        };
+        assert!(matches!(
+            apply_edits(&project(), &[ambiguous]),
+            Err(EditError::SearchAmbiguous { matches: 2, .. })
+        ));
+
+        let unknown: SearchReplace = SearchReplace {
+            path: "src/routes.rs".to_string(),
+            search: "fn routes".to_string(),
+            replace: "".to_string(),
+        };
+        assert_eq!(
+            apply_edits(&project(), &[unknown]),
+            Err(EditError::UnknownFile("src/routes.rs".to_string()))
+        );
+    }
+
+    #[test]
+    fn tests_malformed_edits_are_rejected() {
+        assert_eq!(parse_edits("fn main() {}"), Err(EditError::NoEdits));
+        assert!(matches!(
+            parse_edits("=== EDIT: src/main.rs ===\n<<<<<<< SEARCH\nfn main() {}\n"),
+            Err(EditError::Malformed(_))
+        ));
+        assert!(matches!(
+            parse_edits("=== EDIT: src/main.rs ===\nfn main() {}\n=== END EDIT ==="),
+            Err(EditError::Malformed(_))
+        ));
+    }
+}
@@ -2,9 +2,12 @@ use crossterm::{
     style::{Color, ResetColor, SetForegroundColor},
     ExecutableCommand,
 };
-use std::io::{stdin, stdout};
+use std::{
+    env,
+    io::{self, stdin, stdout},
+};
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone, Copy)]
 pub enum PrintCommand {
     AICall,
     UnitTest,
@@ -13,6 +16,7 @@ pub enum PrintCommand {
 
 impl PrintCommand {
     pub fn print_agent_message(&self, agent_pos: &str, agent_statement: &str) {
+        // Colours are cosmetic, so a terminal that rejects them is ignored.
         let mut stdout: std::io::Stdout = stdout();
 
         let statement_color: Color = match self {
@@ -21,62 +25,69 @@ impl PrintCommand {
             Self::Issue => Color::Red,
         };
 
-        stdout.execute(SetForegroundColor(Color::Green)).unwrap();
+        stdout.execute(SetForegroundColor(Color::Green)).ok();
         print!("Agent: {}: ", agent_pos);
 
-        stdout.execute(SetForegroundColor(statement_color)).unwrap();
+        stdout.execute(SetForegroundColor(statement_color)).ok();
         println!("{}", agent_statement);
 
-        stdout.execute(ResetColor).unwrap();
+        stdout.execute(ResetColor).ok();
     }
 }
 
-pub fn get_user_response(question: &str) -> String {
+pub fn get_cli_option(name: &str) -> Option<String> {
+    let args: Vec<String> = env::args().collect();
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|index| args.get(index + 1))
+        .cloned()
+}
+
+pub fn get_user_response(question: &str) -> io::Result<String> {
     let mut stdout: std::io::Stdout = stdout();
 
-    stdout.execute(SetForegroundColor(Color::Blue)).unwrap();
-    println!("");
+    stdout.execute(SetForegroundColor(Color::Blue)).ok();
+    println!();
     println!("{}", question);
 
-    stdout.execute(ResetColor).unwrap();
+    stdout.execute(ResetColor).ok();
 
     let mut user_response: String = String::new();
-    stdin()
-        .read_line(&mut user_response)
-        .expect("Failed to read response");
+    stdin().read_line(&mut user_response)?;
 
-    return user_response.trim().to_string();
+    Ok(user_response.trim().to_string())
 }
 
-pub fn confirm_safe_code() -> bool {
+pub fn confirm_safe_code() -> io::Result<bool> {
     let mut stdout: std::io::Stdout = stdout();
 
     loop {
-        stdout.execute(SetForegroundColor(Color::Blue)).unwrap();
-        println!("");
+        stdout.execute(SetForegroundColor(Color::Blue)).ok();
+        println!();
         println!("WARNING: You are about to run code written entirely by AI.");
         println!("Review your code and confirm you wish to continue.");
 
-        stdout.execute(ResetColor).unwrap();
+        stdout.execute(ResetColor).ok();
 
-        stdout.execute(SetForegroundColor(Color::Green)).unwrap();
+        stdout.execute(SetForegroundColor(Color::Green)).ok();
         println!("[1] All good");
 
-        stdout.execute(SetForegroundColor(Color::DarkRed)).unwrap();
+        stdout.execute(SetForegroundColor(Color::DarkRed)).ok();
         println!("[2] Lets stop this project");
 
-        stdout.execute(ResetColor).unwrap();
+        stdout.execute(ResetColor).ok();
 
         let mut human_response: String = String::new();
-        stdin()
-            .read_line(&mut human_response)
-            .expect("Failed to read response");
+        if stdin().read_line(&mut human_response)? == 0 {
+            // Nobody is there to confirm, so nothing runs.
+            return Ok(false);
+        }
 
         let human_response: String = human_response.trim().to_lowercase();
 
         match human_response.as_str() {
-            "1" | "ok" | "y" => return true,
-            "2" | "no" | "n" => return false,
+            "1" | "ok" | "y" => return Ok(true),
+            "2" | "no" | "n" => return Ok(false),
             _ => {
                 println!("Invalid input. Please select '1' or '2'.");
             }
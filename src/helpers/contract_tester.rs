@@ -0,0 +1,490 @@
+use crate::models::general::api_schema::{template_params, FieldType, HttpMethod, RouteObject};
+use reqwest::{Client, Method};
+use serde_json::{Map, Value};
+use std::{collections::HashMap, time::Duration};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const SAMPLE_STRING: &str = "sample";
+const DEFAULT_PATH_PARAM: &str = "1";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndpointResult {
+    pub method: HttpMethod,
+    pub route: String,
+    pub url: String,
+    pub status: Option<u16>,
+    pub passed: bool,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContractReport {
+    pub results: Vec<EndpointResult>,
+}
+
+impl ContractReport {
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|result| result.passed)
+    }
+
+    pub fn failures(&self) -> Vec<&EndpointResult> {
+        self.results
+            .iter()
+            .filter(|result| !result.passed)
+            .collect()
+    }
+
+    pub fn render_table(&self) -> String {
+        let mut table: String =
+            String::from("| Method | Route | Status | Result | Detail |\n|---|---|---|---|---|\n");
+        for result in &self.results {
+            table.push_str(&format!(
+                "| {} | `{}` | {} | {} | {} |\n",
+                result.method,
+                result.route,
+                result
+                    .status
+                    .map(|status| status.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                if result.passed { "pass" } else { "FAIL" },
+                result.detail.replace('|', "\\|").replace('\n', " ")
+            ));
+        }
+        table
+    }
+}
+
+// Exercises every route in an order that lets later requests reuse what earlier ones created:
+// creates first, then reads, updates and finally deletes. Values seen in request and response
+// bodies (e.g. an "id") fill the `{id}` segments of later routes.
+pub async fn run_contract_tests(
+    client: &Client,
+    base_url: &str,
+    routes: &[RouteObject],
+) -> ContractReport {
+    let mut tester: ContractTester = ContractTester::default();
+    let mut report: ContractReport = ContractReport::default();
+
+    for route in ordered_routes(routes) {
+        let url: String = format!(
+            "{}{}",
+            base_url.trim_end_matches('/'),
+            tester.fill_path(route)
+        );
+        let request_body: Option<Value> = route.request_body.as_ref().map(synthesize_value);
+
+        let mut request = client
+            .request(reqwest_method(route.method), &url)
+            .timeout(REQUEST_TIMEOUT);
+        if let Some(body) = &request_body {
+            request = request.json(body);
+        }
+
+        let result: EndpointResult = match request.send().await {
+            Ok(response) => {
+                let status: u16 = response.status().as_u16();
+                let body: String = response.text().await.unwrap_or_default();
+                let checked: Result<Option<Value>, String> = if (200..300).contains(&status) {
+                    check_response(&body, route.response.as_ref())
+                } else {
+                    Err(format!("unexpected status {}: {}", status, truncate(&body)))
+                };
+
+                if checked.is_ok() {
+                    if let Some(body) = &request_body {
+                        tester.capture(body);
+                    }
+                }
+                if let Ok(Some(response_body)) = &checked {
+                    tester.capture(response_body);
+                }
+
+                EndpointResult {
+                    method: route.method,
+                    route: route.route.clone(),
+                    url,
+                    status: Some(status),
+                    passed: checked.is_ok(),
+                    detail: checked.err().unwrap_or_default(),
+                }
+            }
+            Err(e) => EndpointResult {
+                method: route.method,
+                route: route.route.clone(),
+                url,
+                status: None,
+                passed: false,
+                detail: format!("request failed: {}", e),
+            },
+        };
+        report.results.push(result);
+    }
+
+    report
+}
+
+#[derive(Debug, Default)]
+struct ContractTester {
+    captured: HashMap<String, String>,
+}
+
+impl ContractTester {
+    // Remembers top level scalar fields, looking into the first element of array bodies.
+    fn capture(&mut self, body: &Value) {
+        let fields: Option<&Map<String, Value>> = match body {
+            Value::Object(fields) => Some(fields),
+            Value::Array(items) => items.first().and_then(Value::as_object),
+            _ => None,
+        };
+
+        for (name, value) in fields.into_iter().flatten() {
+            let captured: Option<String> = match value {
+                Value::String(text) => Some(text.clone()),
+                Value::Number(number) => Some(number.to_string()),
+                _ => None,
+            };
+            if let Some(captured) = captured {
+                self.captured.insert(name.clone(), captured);
+            }
+        }
+    }
+
+    fn fill_path(&self, route: &RouteObject) -> String {
+        let mut path: String = route.route.clone();
+        // Back to front, so the earlier spans stay valid as the lengths change.
+        for (span, param) in template_params(&route.route).into_iter().rev() {
+            let value: &str = self
+                .captured
+                .get(param)
+                .or_else(|| self.captured.get("id"))
+                .map(String::as_str)
+                .unwrap_or(DEFAULT_PATH_PARAM);
+            path.replace_range(span, value);
+        }
+        path
+    }
+}
+
+fn ordered_routes(routes: &[RouteObject]) -> Vec<&RouteObject> {
+    let method_rank = |method: HttpMethod| match method {
+        HttpMethod::Post => 0,
+        HttpMethod::Get => 1,
+        HttpMethod::Head => 2,
+        HttpMethod::Options => 3,
+        HttpMethod::Put => 4,
+        HttpMethod::Patch => 5,
+        HttpMethod::Delete => 6,
+    };
+
+    let mut ordered: Vec<&RouteObject> = routes.iter().collect();
+    ordered.sort_by_key(|route| (method_rank(route.method), route.is_route_dynamic));
+    ordered
+}
+
+fn reqwest_method(method: HttpMethod) -> Method {
+    match method {
+        HttpMethod::Get => Method::GET,
+        HttpMethod::Post => Method::POST,
+        HttpMethod::Put => Method::PUT,
+        HttpMethod::Patch => Method::PATCH,
+        HttpMethod::Delete => Method::DELETE,
+        HttpMethod::Head => Method::HEAD,
+        HttpMethod::Options => Method::OPTIONS,
+    }
+}
+
+fn check_response(body: &str, shape: Option<&FieldType>) -> Result<Option<Value>, String> {
+    let parsed: Option<Value> = serde_json::from_str(body).ok();
+    match (shape, parsed) {
+        (None, parsed) => Ok(parsed),
+        (Some(FieldType::Any), parsed) => Ok(parsed),
+        // Plain text bodies satisfy a string response.
+        (Some(FieldType::String), None) => Ok(None),
+        (Some(shape), Some(value)) => validate_shape(&value, shape, "$").map(|_| Some(value)),
+        (Some(_), None) => Err(format!("response is not JSON: {}", truncate(body))),
+    }
+}
+
+pub fn synthesize_value(shape: &FieldType) -> Value {
+    match shape {
+        FieldType::String => Value::from(SAMPLE_STRING),
+        FieldType::Number => Value::from(1),
+        FieldType::Bool => Value::from(true),
+        FieldType::Any => Value::Null,
+        FieldType::Array(item) => Value::Array(vec![synthesize_value(item)]),
+        FieldType::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(name, field)| (name.clone(), synthesize_value(field)))
+                .collect(),
+        ),
+        FieldType::Optional(inner) => synthesize_value(inner),
+    }
+}
+
+pub fn validate_shape(value: &Value, shape: &FieldType, path: &str) -> Result<(), String> {
+    let mismatch = |expected: &str| {
+        Err(format!(
+            "{}: expected {}, got {}",
+            path,
+            expected,
+            truncate(&value.to_string())
+        ))
+    };
+
+    match (shape, value) {
+        (FieldType::Any, _) => Ok(()),
+        (FieldType::Optional(_), Value::Null) => Ok(()),
+        (FieldType::Optional(inner), _) => validate_shape(value, inner, path),
+        (FieldType::String, Value::String(_)) => Ok(()),
+        (FieldType::Number, Value::Number(_)) => Ok(()),
+        (FieldType::Bool, Value::Bool(_)) => Ok(()),
+        (FieldType::Array(item), Value::Array(items)) => {
+            for (i, element) in items.iter().enumerate() {
+                validate_shape(element, item, &format!("{}[{}]", path, i))?;
+            }
+            Ok(())
+        }
+        (FieldType::Object(fields), Value::Object(object)) => {
+            for (name, field) in fields {
+                let field_path: String = format!("{}.{}", path, name);
+                match object.get(name) {
+                    Some(field_value) => validate_shape(field_value, field, &field_path)?,
+                    None if matches!(field, FieldType::Optional(_)) => {}
+                    None => return Err(format!("{}: missing field", field_path)),
+                }
+            }
+            Ok(())
+        }
+        (FieldType::String, _) => mismatch("string"),
+        (FieldType::Number, _) => mismatch("number"),
+        (FieldType::Bool, _) => mismatch("bool"),
+        (FieldType::Array(_), _) => mismatch("array"),
+        (FieldType::Object(_), _) => mismatch("object"),
+    }
+}
+
+fn truncate(text: &str) -> String {
+    const MAX_CHARS: usize = 80;
+    if text.chars().count() <= MAX_CHARS {
+        return text.to_string();
+    }
+    format!("{}...", text.chars().take(MAX_CHARS).collect::<String>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::{
+        collections::BTreeMap,
+        sync::{Arc, Mutex},
+    };
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    fn task() -> FieldType {
+        FieldType::Object(BTreeMap::from([
+            ("id".to_string(), FieldType::Number),
+            ("name".to_string(), FieldType::String),
+            (
+                "done".to_string(),
+                FieldType::Optional(Box::new(FieldType::Bool)),
+            ),
+        ]))
+    }
+
+    #[test]
+    fn tests_values_are_synthesized_and_validated_against_shapes() {
+        let body: Value = synthesize_value(&task());
+        assert_eq!(body, json!({"id": 1, "name": "sample", "done": true}));
+        assert_eq!(validate_shape(&body, &task(), "$"), Ok(()));
+
+        let list: FieldType = FieldType::Array(Box::new(task()));
+        assert_eq!(
+            validate_shape(&json!([{"id": 1, "name": "a"}, {"id": 2}]), &list, "$"),
+            Err("$[1].name: missing field".to_string())
+        );
+        assert_eq!(
+            validate_shape(&json!({"id": "1", "name": "a"}), &task(), "$"),
+            Err("$.id: expected number, got \"1\"".to_string())
+        );
+        assert_eq!(
+            validate_shape(&json!({"id": 1, "name": "a", "done": null}), &task(), "$"),
+            Ok(())
+        );
+
+        assert_eq!(
+            check_response("Logged in!", Some(&FieldType::String)),
+            Ok(None)
+        );
+        assert!(check_response("Logged in!", Some(&task())).is_err());
+    }
+
+    #[test]
+    fn tests_path_params_are_filled_from_earlier_bodies() {
+        let mut tester: ContractTester = ContractTester::default();
+        let route: RouteObject =
+            RouteObject::new(HttpMethod::Get, "/users/{user}/tasks/{id}", None, None);
+        assert_eq!(tester.fill_path(&route), "/users/1/tasks/1");
+
+        tester.capture(&json!([{"id": 42, "user": "ada", "tags": ["x"]}]));
+        assert_eq!(tester.fill_path(&route), "/users/ada/tasks/42");
+
+        let route: RouteObject = RouteObject::new(HttpMethod::Get, "/a/{idx}/b/{ id }", None, None);
+        tester.capture(&json!({"idx": 7}));
+        assert_eq!(tester.fill_path(&route), "/a/7/b/42");
+    }
+
+    #[test]
+    fn tests_routes_are_ordered_create_read_update_delete() {
+        let routes: Vec<RouteObject> = vec![
+            RouteObject::new(HttpMethod::Delete, "/task/{id}", None, None),
+            RouteObject::new(HttpMethod::Get, "/task/{id}", None, None),
+            RouteObject::new(HttpMethod::Put, "/task", None, None),
+            RouteObject::new(HttpMethod::Get, "/task", None, None),
+            RouteObject::new(HttpMethod::Post, "/task", None, None),
+        ];
+        let order: Vec<String> = ordered_routes(&routes)
+            .iter()
+            .map(|route| format!("{} {}", route.method, route.route))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                "POST /task",
+                "GET /task",
+                "GET /task/{id}",
+                "PUT /task",
+                "DELETE /task/{id}"
+            ]
+        );
+    }
+
+    async fn read_request(stream: &mut tokio::net::TcpStream) -> String {
+        let mut request: Vec<u8> = vec![];
+        let mut buffer: [u8; 1024] = [0; 1024];
+        loop {
+            let read: usize = stream.read(&mut buffer).await.unwrap_or_default();
+            request.extend_from_slice(&buffer[..read]);
+
+            let text: String = String::from_utf8_lossy(&request).to_string();
+            if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                let content_length: usize = head
+                    .lines()
+                    .find_map(|line| {
+                        line.to_lowercase()
+                            .strip_prefix("content-length:")
+                            .map(|v| v.trim().to_string())
+                    })
+                    .and_then(|length| length.parse().ok())
+                    .unwrap_or_default();
+                if body.len() >= content_length {
+                    return text;
+                }
+            }
+            if read == 0 {
+                return text;
+            }
+        }
+    }
+
+    // Serves canned responses keyed by "METHOD /path" and records the requests it received.
+    async fn stub_server(
+        responses: Vec<(&'static str, u16, &'static str)>,
+    ) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url: String = format!("http://{}", listener.local_addr().unwrap());
+        let received: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(vec![]));
+        let log: Arc<Mutex<Vec<String>>> = received.clone();
+
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let request: String = read_request(&mut stream).await;
+                let request_line: String = request
+                    .split(" HTTP/")
+                    .next()
+                    .unwrap_or_default()
+                    .to_string();
+                log.lock().unwrap().push(request.clone());
+
+                let (status, body): (u16, &str) = responses
+                    .iter()
+                    .find(|(key, _, _)| *key == request_line)
+                    .map(|(_, status, body)| (*status, *body))
+                    .unwrap_or((404, ""));
+                let response: String = format!(
+                    "HTTP/1.1 {} X\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+
+        (base_url, received)
+    }
+
+    #[tokio::test]
+    async fn tests_contract_tests_exercise_every_route() {
+        let (base_url, received) = stub_server(vec![
+            ("POST /task", 200, ""),
+            ("GET /task", 200, r#"[{"id": 1, "name": "sample"}]"#),
+            ("GET /task/1", 200, r#"{"id": 1, "name": 7}"#),
+            ("DELETE /task/1", 500, "boom"),
+        ])
+        .await;
+
+        let routes: Vec<RouteObject> = vec![
+            RouteObject::new(HttpMethod::Delete, "/task/{id}", None, None),
+            RouteObject::new(HttpMethod::Get, "/task/{id}", None, Some(task())),
+            RouteObject::new(
+                HttpMethod::Get,
+                "/task",
+                None,
+                Some(FieldType::Array(Box::new(task()))),
+            ),
+            RouteObject::new(HttpMethod::Post, "/task", Some(task()), None),
+        ];
+
+        let report: ContractReport = run_contract_tests(&Client::new(), &base_url, &routes).await;
+
+        let outcomes: Vec<(String, Option<u16>, bool)> = report
+            .results
+            .iter()
+            .map(|result| {
+                (
+                    format!("{} {}", result.method, result.route),
+                    result.status,
+                    result.passed,
+                )
+            })
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                ("POST /task".to_string(), Some(200), true),
+                ("GET /task".to_string(), Some(200), true),
+                ("GET /task/{id}".to_string(), Some(200), false),
+                ("DELETE /task/{id}".to_string(), Some(500), false),
+            ]
+        );
+        assert!(!report.passed());
+        assert_eq!(report.failures().len(), 2);
+        assert_eq!(report.results[2].detail, "$.name: expected string, got 7");
+
+        let requests: Vec<String> = received.lock().unwrap().clone();
+        assert!(requests[0].ends_with(r#"{"done":true,"id":1,"name":"sample"}"#));
+
+        let table: String = report.render_table();
+        assert!(
+            table.contains("| GET | `/task/{id}` | 200 | FAIL | $.name: expected string, got 7 |")
+        );
+        assert!(
+            table.contains("| DELETE | `/task/{id}` | 500 | FAIL | unexpected status 500: boom |")
+        );
+    }
+}
@@ -1,22 +1,29 @@
-use std::fs;
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use reqwest::Client;
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
 
-use super::command_line::PrintCommand;
-use crate::{apis::call_request::call_gpt, models::general::llm::Message};
+use super::{command_line::PrintCommand, scaffold::GENERATED_PACKAGE_NAME, workspace::Workspace};
+use crate::models::{
+    agents::agent_context::AgentContext,
+    general::{
+        api_schema::RouteObject,
+        candidate_score::CandidateScore,
+        diagnostics::{BuildDiagnostics, Diagnostic, StyleReport},
+        error::AutoGptError,
+        llm::Message,
+        openapi::openapi_document,
+        project_files::ProjectFiles,
+    },
+};
 
-const CODE_TEMPLATE_PATH: &str =
-    "/Users/por-livinginsider/Desktop/living-projects/basic_auto_gpt/web_gpt_template/src/code_template.rs";
-
-pub const EXEC_MAIN_PATH: &str =
-    "/Users/por-livinginsider/Desktop/living-projects/basic_auto_gpt/web_gpt_template/src/main.rs";
-
-pub const WEB_SERVER_PROJECT_PATH: &str =
-    "/Users/por-livinginsider/Desktop/living-projects/basic_auto_gpt/web_gpt_template/";
-
-const API_SCHEMA_PATH: &str =
-    "/Users/por-livinginsider/Desktop/living-projects/basic_auto_gpt/schemas/api_schema.rs";
+const STATUS_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
 
 pub fn extend_ai_function(ai_func: fn(&str) -> &'static str, func_input: &str) -> Message {
     let ai_function_str = ai_func(func_input);
@@ -37,70 +44,258 @@ pub fn extend_ai_function(ai_func: fn(&str) -> &'static str, func_input: &str) -
 }
 
 pub async fn ai_task_request(
+    context: &AgentContext,
+    msg_context: String,
+    agent_position: &str,
+    agent_operation: &str,
+    function_pass: for<'a> fn(&'a str) -> &'static str,
+) -> Result<String, AutoGptError> {
+    ai_task_request_with_model(
+        context,
+        msg_context,
+        agent_position,
+        agent_operation,
+        function_pass,
+        &context.config.model,
+    )
+    .await
+}
+
+pub async fn ai_task_request_with_model(
+    context: &AgentContext,
     msg_context: String,
     agent_position: &str,
     agent_operation: &str,
     function_pass: for<'a> fn(&'a str) -> &'static str,
-) -> String {
+    model: &str,
+) -> Result<String, AutoGptError> {
+    context.cancellation.check()?;
+
+    let msg_context: String = match &context.config.objective {
+        Some(objective) => format!("{}\nYour objective: {}", msg_context, objective),
+        None => msg_context,
+    };
     let extended_msg: Message = extend_ai_function(function_pass, &msg_context);
 
-    PrintCommand::AICall.print_agent_message(agent_position, agent_operation);
+    context.emit(PrintCommand::AICall, agent_position, agent_operation);
 
-    let llm_response_res: Result<String, Box<dyn std::error::Error + Send>> =
-        call_gpt(vec![extended_msg.clone()]).await;
+    let mut llm_response_res: Result<String, AutoGptError> = context
+        .provider
+        .chat(vec![extended_msg.clone()], model)
+        .await;
 
-    match llm_response_res {
-        Ok(llm_resp) => llm_resp,
-        Err(_) => call_gpt(vec![extended_msg.clone()])
-            .await
-            .expect("Failed twice to call GPT"),
+    // A missing key will not fix itself; failed requests are retried.
+    for _ in 0..context.config.llm_retries {
+        if !matches!(llm_response_res, Err(AutoGptError::Llm(_))) {
+            break;
+        }
+        llm_response_res = context
+            .provider
+            .chat(vec![extended_msg.clone()], model)
+            .await;
     }
+    llm_response_res
 }
 
 pub async fn ai_task_request_decoded<T: DeserializeOwned>(
+    context: &AgentContext,
     msg_context: String,
     agent_position: &str,
     agent_operation: &str,
     function_pass: for<'a> fn(&'a str) -> &'static str,
-) -> T {
-    let llm_response: String =
-        ai_task_request(msg_context, agent_position, agent_operation, function_pass).await;
-
-    let decoded_response: T = serde_json::from_str(llm_response.as_str())
-        .expect("Failed to decoded ai response from serde_json");
+) -> Result<T, AutoGptError> {
+    let llm_response: String = ai_task_request(
+        context,
+        msg_context,
+        agent_position,
+        agent_operation,
+        function_pass,
+    )
+    .await?;
 
-    decoded_response
+    serde_json::from_str(llm_response.as_str()).map_err(|e| AutoGptError::Decode {
+        operation: agent_operation.to_string(),
+        reason: e.to_string(),
+    })
 }
 
 pub async fn check_status_code(client: &Client, url: &str) -> Result<u16, reqwest::Error> {
-    let response: reqwest::Response = client.get(url).send().await?;
+    let response: reqwest::Response = client.get(url).timeout(STATUS_CHECK_TIMEOUT).send().await?;
     Ok(response.status().as_u16())
 }
 
-pub fn read_code_template_contents() -> String {
-    let path: String = String::from(CODE_TEMPLATE_PATH);
-    fs::read_to_string(path).expect("Failed to read code template.")
+pub fn read_code_template_contents(workspace: &Workspace) -> Result<String, AutoGptError> {
+    fs::read_to_string(workspace.code_template_path())
+        .map_err(AutoGptError::io("read the code template"))
 }
 
-pub fn read_exec_main_contents() -> String {
-    let path: String = String::from(EXEC_MAIN_PATH);
-    fs::read_to_string(path).expect("Failed to read code template.")
+pub fn read_backend_files(workspace: &Workspace) -> Result<ProjectFiles, AutoGptError> {
+    read_backend_files_in(workspace).map_err(AutoGptError::io("read the backend source files"))
 }
 
-pub fn save_backend_code(contents: &String) {
-    let path: String = String::from(EXEC_MAIN_PATH);
-    fs::write(path, contents).expect("Failed to write main.rs file.")
+fn read_backend_files_in(workspace: &Workspace) -> io::Result<ProjectFiles> {
+    let mut files: ProjectFiles = ProjectFiles::default();
+    let mut dirs: Vec<PathBuf> = vec![workspace.file_path("src")];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path: PathBuf = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            if path == workspace.code_template_path()
+                || path.extension().is_none_or(|extension| extension != "rs")
+            {
+                continue;
+            }
+
+            let relative: String = path
+                .strip_prefix(workspace.root())
+                .map_err(io::Error::other)?
+                .to_string_lossy()
+                .replace('\\', "/");
+            let contents: String = fs::read_to_string(&path)?;
+            files
+                .insert(&relative, contents)
+                .map_err(io::Error::other)?;
+        }
+    }
+
+    Ok(files)
+}
+
+pub fn save_backend_files(workspace: &Workspace, files: &ProjectFiles) -> Result<(), AutoGptError> {
+    for (relative, contents) in files.iter() {
+        write_file(&workspace.file_path(relative), contents)
+            .map_err(AutoGptError::io("write the backend source files"))?;
+    }
+    Ok(())
+}
+
+pub fn remove_backend_files(workspace: &Workspace, paths: &[String]) -> Result<(), AutoGptError> {
+    for relative in paths {
+        let path: PathBuf = workspace.file_path(relative);
+        if path.exists() {
+            fs::remove_file(path).map_err(AutoGptError::io("remove stale backend source files"))?;
+        }
+    }
+    Ok(())
+}
+
+pub fn save_api_schema(
+    workspace: &Workspace,
+    description: &str,
+    api_endpoints: &[RouteObject],
+) -> Result<(), AutoGptError> {
+    let document: Value = openapi_document(GENERATED_PACKAGE_NAME, description, api_endpoints);
+
+    serde_json::to_string_pretty(&document)
+        .map_err(io::Error::from)
+        .and_then(|json| write_file(&workspace.openapi_json_path(), &json))
+        .map_err(AutoGptError::io("write the OpenAPI JSON file"))?;
+
+    serde_yaml::to_string(&document)
+        .map_err(io::Error::other)
+        .and_then(|yaml| write_file(&workspace.openapi_yaml_path(), &yaml))
+        .map_err(AutoGptError::io("write the OpenAPI YAML file"))
+}
+
+pub fn save_contract_report(
+    workspace: &Workspace,
+    contract_table: &str,
+) -> Result<(), AutoGptError> {
+    write_file(&workspace.contract_report_path(), contract_table)
+        .map_err(AutoGptError::io("write the contract report"))
+}
+
+fn write_file(path: &Path, contents: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)
 }
 
-pub fn save_api_endpoints(api_endpoints: &String) {
-    let path: String = String::from(API_SCHEMA_PATH);
-    fs::write(path, api_endpoints).expect("Failed to write API Endpoints  file.")
+// Appends one line per build to the diagnostics log and returns the recorded build.
+pub fn save_build_diagnostics(
+    workspace: &Workspace,
+    diagnostics: Vec<Diagnostic>,
+) -> Result<BuildDiagnostics, AutoGptError> {
+    let record: BuildDiagnostics = BuildDiagnostics {
+        build: read_build_diagnostics(workspace).len() + 1,
+        diagnostics,
+    };
+    append_json_line(&workspace.diagnostics_log_path(), &record)
+        .map_err(AutoGptError::io("write the build diagnostics log"))?;
+    Ok(record)
+}
+
+pub fn read_build_diagnostics(workspace: &Workspace) -> Vec<BuildDiagnostics> {
+    read_json_lines(&workspace.diagnostics_log_path())
+}
+
+// Appends one line per rustfmt and clippy pass to the style log and returns the recorded pass.
+pub fn save_style_report(
+    workspace: &Workspace,
+    reformatted_files: Vec<String>,
+    lints: Vec<Diagnostic>,
+) -> Result<StyleReport, AutoGptError> {
+    let record: StyleReport = StyleReport {
+        round: read_style_reports(workspace).len() + 1,
+        reformatted_files,
+        lints,
+    };
+    append_json_line(&workspace.style_log_path(), &record)
+        .map_err(AutoGptError::io("write the style log"))?;
+    Ok(record)
+}
+
+pub fn read_style_reports(workspace: &Workspace) -> Vec<StyleReport> {
+    read_json_lines(&workspace.style_log_path())
+}
+
+fn append_json_line<T: Serialize>(path: &Path, record: &T) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let line: String = serde_json::to_string(record)?;
+    let mut log: fs::File = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(log, "{}", line)
+}
+
+fn read_json_lines<T: DeserializeOwned>(path: &Path) -> Vec<T> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+pub fn save_candidate_scores(
+    workspace: &Workspace,
+    scores: &[CandidateScore],
+) -> Result<(), AutoGptError> {
+    serde_json::to_string_pretty(scores)
+        .map_err(io::Error::from)
+        .and_then(|json| write_file(&workspace.candidate_scores_path(), &json))
+        .map_err(AutoGptError::io("write the candidate scores"))
+}
+
+pub fn read_candidate_scores(workspace: &Workspace) -> Vec<CandidateScore> {
+    fs::read_to_string(workspace.candidate_scores_path())
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ai_functions::aifunc_managing::convert_user_input_to_goal;
+    use crate::{
+        ai_functions::aifunc_managing::convert_user_input_to_goal,
+        helpers::workspace::new_run_id,
+        models::{agents::agent_context::CancellationToken, general::project_files::MAIN_FILE},
+    };
 
     #[test]
     fn test_extending_ai_function() {
@@ -110,17 +305,69 @@ mod tests {
         assert_eq!(extended_msg.role, "system".to_string());
     }
 
+    #[test]
+    fn tests_backend_files_round_trip_through_workspace() {
+        let base_dir: PathBuf = std::env::temp_dir().join(format!("general-{}", new_run_id()));
+        let template_dir: PathBuf =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("web_gpt_template");
+        let workspace: Workspace = Workspace::create_in(&base_dir, &template_dir, "run").unwrap();
+
+        let mut backend_files: ProjectFiles = ProjectFiles::default();
+        backend_files
+            .insert(MAIN_FILE, "mod handlers;\nfn main() {}\n".to_string())
+            .unwrap();
+        backend_files
+            .insert("src/handlers/mod.rs", "pub fn index() {}\n".to_string())
+            .unwrap();
+        save_backend_files(&workspace, &backend_files).unwrap();
+
+        assert_eq!(read_backend_files(&workspace).unwrap(), backend_files);
+
+        remove_backend_files(&workspace, &["src/handlers/mod.rs".to_string()]).unwrap();
+        assert_eq!(
+            read_backend_files(&workspace).unwrap().paths(),
+            vec![MAIN_FILE]
+        );
+
+        let _ = fs::remove_dir_all(&base_dir);
+    }
+
+    #[test]
+    fn tests_build_diagnostics_are_numbered_per_build() {
+        let base_dir: PathBuf = std::env::temp_dir().join(format!("diagnostics-{}", new_run_id()));
+        let template_dir: PathBuf =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("web_gpt_template");
+        let workspace: Workspace = Workspace::create_in(&base_dir, &template_dir, "run").unwrap();
+
+        assert_eq!(save_build_diagnostics(&workspace, vec![]).unwrap().build, 1);
+        assert_eq!(save_build_diagnostics(&workspace, vec![]).unwrap().build, 2);
+        assert_eq!(read_build_diagnostics(&workspace).len(), 2);
+
+        let _ = fs::remove_dir_all(&base_dir);
+    }
+
     #[tokio::test]
     async fn tests_ai_task_request() {
+        let base_dir: PathBuf = std::env::temp_dir().join(format!("ai-task-{}", new_run_id()));
+        let template_dir: PathBuf =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("web_gpt_template");
+        let workspace: Workspace = Workspace::create_in(&base_dir, &template_dir, "run").unwrap();
+        let context: AgentContext =
+            AgentContext::from_env(workspace, CancellationToken::default()).unwrap();
+
         let ai_func_param: String =
             "Build me a webserver for making stock price api request.".to_string();
         let res = ai_task_request(
+            &context,
             ai_func_param,
             "Managing Agent",
             "Defining user requirements",
             convert_user_input_to_goal,
         )
-        .await;
+        .await
+        .expect("Failed to call the model");
         assert!(res.len() > 20);
+
+        let _ = fs::remove_dir_all(&base_dir);
     }
 }
@@ -0,0 +1,253 @@
+use std::{
+    io,
+    mem::{self, MaybeUninit},
+    net::Ipv4Addr,
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+};
+use tokio::{
+    io::{copy_bidirectional, AsyncReadExt},
+    net::{TcpListener, UnixStream},
+};
+
+// A sandboxed server listens on the loopback device of its own network namespace, which the agent
+// cannot reach. A relay forked inside the sandbox accepts connections on a Unix socket in the
+// workspace, connects each one to the server and answers with a status byte before passing
+// bytes through, so the agent can tell a listening server from a relay that is merely up.
+const STATUS_CONNECTED: u8 = b'1';
+const MAX_CONNECTIONS: usize = 32;
+const BUFFER_SIZE: usize = 16 * 1024;
+
+pub async fn is_ready(socket_path: &Path) -> bool {
+    connect(socket_path).await.is_ok()
+}
+
+// Accepts connections on the agent's side and tunnels each one into the sandbox.
+pub async fn forward(listener: TcpListener, socket_path: PathBuf) {
+    while let Ok((mut client, _)) = listener.accept().await {
+        let socket_path: PathBuf = socket_path.clone();
+        tokio::spawn(async move {
+            if let Ok(mut upstream) = connect(&socket_path).await {
+                let _ = copy_bidirectional(&mut client, &mut upstream).await;
+            }
+        });
+    }
+}
+
+async fn connect(socket_path: &Path) -> io::Result<UnixStream> {
+    let mut stream: UnixStream = UnixStream::connect(socket_path).await?;
+    let mut status: [u8; 1] = [0];
+    stream.read_exact(&mut status).await?;
+    if status[0] != STATUS_CONNECTED {
+        return Err(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            "server is not accepting connections",
+        ));
+    }
+    Ok(stream)
+}
+
+pub fn socket_address(socket_path: &Path) -> io::Result<libc::sockaddr_un> {
+    let mut address: libc::sockaddr_un = unsafe { mem::zeroed() };
+    let path: &[u8] = socket_path.as_os_str().as_bytes();
+    if path.len() >= address.sun_path.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("relay socket path is too long: {}", socket_path.display()),
+        ));
+    }
+    address.sun_family = libc::AF_UNIX as libc::sa_family_t;
+    for (slot, byte) in address.sun_path.iter_mut().zip(path) {
+        *slot = *byte as libc::c_char;
+    }
+    Ok(address)
+}
+
+// Runs in a process forked from a multi-threaded one, so it only makes raw system calls and
+// never allocates. It lives until its process group is killed.
+pub unsafe fn serve_inside(address: &libc::sockaddr_un, port: u16) -> ! {
+    // Inherited descriptors include the pipe `Command::spawn` waits on until the exec, which this
+    // process never does.
+    if libc::syscall(libc::SYS_close_range, 3, libc::c_uint::MAX, 0) != 0 {
+        for fd in 3..1024 {
+            libc::close(fd);
+        }
+    }
+
+    let listener: libc::c_int = libc::socket(libc::AF_UNIX, libc::SOCK_STREAM, 0);
+    if listener < 0
+        || libc::bind(
+            listener,
+            address as *const libc::sockaddr_un as *const libc::sockaddr,
+            mem::size_of::<libc::sockaddr_un>() as libc::socklen_t,
+        ) != 0
+        || libc::listen(listener, MAX_CONNECTIONS as libc::c_int) != 0
+    {
+        libc::_exit(1);
+    }
+
+    let mut pairs: [(libc::c_int, libc::c_int); MAX_CONNECTIONS] = [(-1, -1); MAX_CONNECTIONS];
+    let mut poll_fds: [libc::pollfd; 1 + 2 * MAX_CONNECTIONS] = mem::zeroed();
+    let mut buffer: [MaybeUninit<u8>; BUFFER_SIZE] = [MaybeUninit::uninit(); BUFFER_SIZE];
+
+    loop {
+        poll_fds[0] = libc::pollfd {
+            fd: listener,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        for (i, (client, server)) in pairs.iter().enumerate() {
+            poll_fds[1 + 2 * i] = libc::pollfd {
+                fd: *client,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            poll_fds[2 + 2 * i] = libc::pollfd {
+                fd: *server,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+        }
+        if libc::poll(poll_fds.as_mut_ptr(), poll_fds.len() as libc::nfds_t, -1) < 0 {
+            continue;
+        }
+
+        if poll_fds[0].revents != 0 {
+            let client: libc::c_int =
+                libc::accept(listener, std::ptr::null_mut(), std::ptr::null_mut());
+            if client >= 0 {
+                let server: libc::c_int = connect_loopback(port);
+                let free_slot: Option<&mut (libc::c_int, libc::c_int)> =
+                    pairs.iter_mut().find(|(fd, _)| *fd < 0);
+                match free_slot {
+                    Some(slot) if server >= 0 && write_all(client, &[STATUS_CONNECTED]) => {
+                        *slot = (client, server);
+                    }
+                    _ => {
+                        libc::close(client);
+                        if server >= 0 {
+                            libc::close(server);
+                        }
+                    }
+                }
+            }
+        }
+
+        for (i, pair) in pairs.iter_mut().enumerate() {
+            let (client, server) = *pair;
+            if client < 0 {
+                continue;
+            }
+            let mut open: bool = true;
+            for (from, to, revents) in [
+                (client, server, poll_fds[1 + 2 * i].revents),
+                (server, client, poll_fds[2 + 2 * i].revents),
+            ] {
+                if open && revents != 0 {
+                    let read: isize = libc::read(from, buffer.as_mut_ptr().cast(), BUFFER_SIZE);
+                    open = read > 0
+                        && write_all(
+                            to,
+                            std::slice::from_raw_parts(buffer.as_ptr().cast(), read as usize),
+                        );
+                }
+            }
+            if !open {
+                libc::close(client);
+                libc::close(server);
+                *pair = (-1, -1);
+            }
+        }
+    }
+}
+
+unsafe fn connect_loopback(port: u16) -> libc::c_int {
+    let socket: libc::c_int = libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0);
+    if socket < 0 {
+        return -1;
+    }
+    let mut address: libc::sockaddr_in = mem::zeroed();
+    address.sin_family = libc::AF_INET as libc::sa_family_t;
+    address.sin_port = port.to_be();
+    address.sin_addr.s_addr = u32::from(Ipv4Addr::LOCALHOST).to_be();
+
+    let connected: libc::c_int = libc::connect(
+        socket,
+        &address as *const libc::sockaddr_in as *const libc::sockaddr,
+        mem::size_of::<libc::sockaddr_in>() as libc::socklen_t,
+    );
+    if connected != 0 {
+        libc::close(socket);
+        return -1;
+    }
+    socket
+}
+
+unsafe fn write_all(fd: libc::c_int, mut bytes: &[u8]) -> bool {
+    while !bytes.is_empty() {
+        let written: isize = libc::write(fd, bytes.as_ptr().cast(), bytes.len());
+        if written <= 0 {
+            return false;
+        }
+        bytes = &bytes[written as usize..];
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs, net::SocketAddr, process, time::Duration};
+    use tokio::{io::AsyncWriteExt, net::TcpStream, time};
+
+    #[tokio::test]
+    async fn tests_relay_reports_readiness_and_passes_traffic_through() {
+        let socket_path: PathBuf = env::temp_dir().join(format!("relay_{}.sock", process::id()));
+        let _ = fs::remove_file(&socket_path);
+
+        let server: TcpListener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let server_port: u16 = server.local_addr().unwrap().port();
+        drop(server);
+
+        let address: libc::sockaddr_un = socket_address(&socket_path).unwrap();
+        let relay_pid: libc::pid_t = unsafe { libc::fork() };
+        if relay_pid == 0 {
+            unsafe { serve_inside(&address, server_port) };
+        }
+
+        while !socket_path.exists() {
+            time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(!is_ready(&socket_path).await);
+
+        let server: TcpListener = TcpListener::bind((Ipv4Addr::LOCALHOST, server_port))
+            .await
+            .unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = server.accept().await {
+                let mut request: [u8; 4] = [0; 4];
+                if stream.read_exact(&mut request).await.is_ok() {
+                    let _ = stream.write_all(b"pong").await;
+                }
+            }
+        });
+        assert!(is_ready(&socket_path).await);
+
+        let entry: TcpListener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let entry_address: SocketAddr = entry.local_addr().unwrap();
+        tokio::spawn(forward(entry, socket_path.clone()));
+
+        let mut client: TcpStream = TcpStream::connect(entry_address).await.unwrap();
+        client.write_all(b"ping").await.unwrap();
+        let mut response: [u8; 4] = [0; 4];
+        client.read_exact(&mut response).await.unwrap();
+
+        unsafe {
+            libc::kill(relay_pid, libc::SIGKILL);
+            libc::waitpid(relay_pid, std::ptr::null_mut(), 0);
+        }
+        let _ = fs::remove_file(&socket_path);
+
+        assert_eq!(&response, b"pong");
+    }
+}
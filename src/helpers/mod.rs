@@ -1,2 +1,15 @@
+pub mod code_edits;
 pub mod command_line;
+pub mod contract_tester;
 pub mod general;
+#[cfg(target_os = "linux")]
+pub mod loopback_relay;
+pub mod process_supervisor;
+pub mod project_history;
+pub mod route_extraction;
+#[cfg(target_os = "linux")]
+pub mod sandbox;
+pub mod scaffold;
+pub mod server;
+pub mod test_bench;
+pub mod workspace;
@@ -0,0 +1,416 @@
+use std::{
+    env, fmt,
+    fs::{self, File},
+    io,
+    os::unix::process::{CommandExt, ExitStatusExt},
+    path::{Path, PathBuf},
+    process::{self, Child, Command, ExitStatus, Stdio},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::time;
+
+use super::command_line::PrintCommand;
+use crate::models::agents::agent_context::{AgentEvent, CancellationToken, EventSink};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+const DEFAULT_BUILD_TIMEOUT: Duration = Duration::from_secs(600);
+const DEFAULT_RUN_TIMEOUT: Duration = Duration::from_secs(300);
+const SUPERVISOR_POSITION: &str = "Process Supervisor";
+
+// Process groups that are still running, so a Ctrl-C can take them down with the agent.
+static LIVE_GROUPS: Mutex<Vec<i32>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProcessTimeouts {
+    pub build: Duration,
+    pub run: Duration,
+}
+
+impl Default for ProcessTimeouts {
+    fn default() -> Self {
+        Self {
+            build: DEFAULT_BUILD_TIMEOUT,
+            run: DEFAULT_RUN_TIMEOUT,
+        }
+    }
+}
+
+impl ProcessTimeouts {
+    pub fn from_env() -> Self {
+        Self {
+            build: timeout_from_env("BACKEND_BUILD_TIMEOUT_SECS", DEFAULT_BUILD_TIMEOUT),
+            run: timeout_from_env("BACKEND_RUN_TIMEOUT_SECS", DEFAULT_RUN_TIMEOUT),
+        }
+    }
+}
+
+fn timeout_from_env(name: &str, default: Duration) -> Duration {
+    env::var(name)
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(default)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ResourceUsage {
+    pub user_time: Duration,
+    pub system_time: Duration,
+    pub max_rss_kib: i64,
+}
+
+impl ResourceUsage {
+    fn from_rusage(rusage: &libc::rusage) -> Self {
+        let to_duration = |tv: libc::timeval| {
+            Duration::from_secs(tv.tv_sec as u64) + Duration::from_micros(tv.tv_usec as u64)
+        };
+        Self {
+            user_time: to_duration(rusage.ru_utime),
+            system_time: to_duration(rusage.ru_stime),
+            max_rss_kib: rusage.ru_maxrss,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessReport {
+    pub name: String,
+    pub status: ExitStatus,
+    pub timed_out: bool,
+    pub duration: Duration,
+    pub usage: ResourceUsage,
+    pub stdout_log: PathBuf,
+    pub stderr_log: PathBuf,
+}
+
+impl ProcessReport {
+    pub fn success(&self) -> bool {
+        !self.timed_out && self.status.success()
+    }
+
+    pub fn stdout(&self) -> String {
+        fs::read_to_string(&self.stdout_log).unwrap_or_default()
+    }
+
+    pub fn stderr(&self) -> String {
+        fs::read_to_string(&self.stderr_log).unwrap_or_default()
+    }
+}
+
+impl fmt::Display for ProcessReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.timed_out {
+            write!(f, "{}: timed out", self.name)?;
+        } else {
+            write!(f, "{}: {}", self.name, self.status)?;
+        }
+        write!(
+            f,
+            " after {:.1}s (user {:.2}s, sys {:.2}s, max rss {} KiB)",
+            self.duration.as_secs_f64(),
+            self.usage.user_time.as_secs_f64(),
+            self.usage.system_time.as_secs_f64(),
+            self.usage.max_rss_kib
+        )
+    }
+}
+
+// Starts every command in its own process group with both streams going to numbered log files,
+// so a run leaves `01-cargo-build.stdout.log`, `02-cargo-run.stderr.log`, ... behind.
+#[derive(Debug)]
+pub struct ProcessSupervisor {
+    log_dir: PathBuf,
+    next_run: usize,
+}
+
+impl ProcessSupervisor {
+    pub fn new(log_dir: &Path) -> Self {
+        // Picks up numbering after the logs of an earlier, resumed run.
+        let next_run: usize = fs::read_dir(log_dir)
+            .map(|entries| {
+                entries
+                    .filter_map(Result::ok)
+                    .filter(|entry| entry.file_name().to_string_lossy().ends_with(".stderr.log"))
+                    .count()
+            })
+            .unwrap_or(0)
+            + 1;
+
+        Self {
+            log_dir: log_dir.to_path_buf(),
+            next_run,
+        }
+    }
+
+    pub fn spawn(
+        &mut self,
+        name: &str,
+        command: &mut Command,
+        timeout: Duration,
+    ) -> io::Result<SupervisedProcess> {
+        fs::create_dir_all(&self.log_dir)?;
+        let log_stem: String = format!("{:02}-{}", self.next_run, name);
+        self.next_run += 1;
+
+        let stdout_log: PathBuf = self.log_dir.join(format!("{}.stdout.log", log_stem));
+        let stderr_log: PathBuf = self.log_dir.join(format!("{}.stderr.log", log_stem));
+
+        let child: Child = command
+            .stdin(Stdio::null())
+            .stdout(File::create(&stdout_log)?)
+            .stderr(File::create(&stderr_log)?)
+            .process_group(0)
+            .spawn()?;
+        LIVE_GROUPS.lock().unwrap().push(child.id() as i32);
+
+        let started: Instant = Instant::now();
+        Ok(SupervisedProcess {
+            name: name.to_string(),
+            pid: child.id() as i32,
+            started,
+            deadline: started + timeout,
+            stdout_log,
+            stderr_log,
+            report: None,
+        })
+    }
+
+    // Runs the command to completion, killing its process group once the timeout passes.
+    pub async fn run(
+        &mut self,
+        name: &str,
+        command: &mut Command,
+        timeout: Duration,
+    ) -> io::Result<ProcessReport> {
+        let mut process: SupervisedProcess = self.spawn(name, command, timeout)?;
+        loop {
+            if let Some(report) = process.try_finish()? {
+                return Ok(report);
+            }
+            time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+// A running process group. Dropping it kills the whole group, so servers do not outlive an
+// agent that panicked or returned early.
+#[derive(Debug)]
+pub struct SupervisedProcess {
+    name: String,
+    pid: i32,
+    started: Instant,
+    deadline: Instant,
+    stdout_log: PathBuf,
+    stderr_log: PathBuf,
+    report: Option<ProcessReport>,
+}
+
+impl SupervisedProcess {
+    pub fn id(&self) -> u32 {
+        self.pid as u32
+    }
+
+    // Returns the report once the process has exited, or has been killed for running too long.
+    pub fn try_finish(&mut self) -> io::Result<Option<ProcessReport>> {
+        if self.report.is_none() {
+            let timed_out: bool = if has_exited(self.pid)? {
+                false
+            } else if Instant::now() >= self.deadline {
+                true
+            } else {
+                return Ok(None);
+            };
+            self.finish(timed_out)?;
+        }
+        Ok(self.report.clone())
+    }
+
+    pub fn stop(mut self) -> io::Result<ProcessReport> {
+        if self.report.is_none() {
+            self.finish(false)?;
+        }
+        Ok(self.report.clone().unwrap())
+    }
+
+    // The group is killed before the leader is reaped, while its id cannot have been reused,
+    // which also takes down anything the leader left running.
+    fn finish(&mut self, timed_out: bool) -> io::Result<()> {
+        kill_group(self.pid);
+        let (status, usage) = reap(self.pid)?;
+        LIVE_GROUPS.lock().unwrap().retain(|pgid| *pgid != self.pid);
+
+        self.report = Some(ProcessReport {
+            name: self.name.clone(),
+            status,
+            timed_out,
+            duration: self.started.elapsed(),
+            usage,
+            stdout_log: self.stdout_log.clone(),
+            stderr_log: self.stderr_log.clone(),
+        });
+        Ok(())
+    }
+}
+
+impl Drop for SupervisedProcess {
+    fn drop(&mut self) {
+        if self.report.is_none() {
+            let _ = self.finish(false);
+        }
+    }
+}
+
+// Kills every supervised process group and cancels the agents on the first Ctrl-C, so the run
+// stops after its current step with its checkpoint saved; a second Ctrl-C exits at once. The
+// groups do not share the terminal's foreground group, so they would not see the signal themselves.
+pub fn kill_on_ctrl_c(cancellation: CancellationToken, events: Arc<dyn EventSink>) {
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        for pgid in LIVE_GROUPS.lock().unwrap().iter() {
+            kill_group(*pgid);
+        }
+        cancellation.cancel();
+        events.emit(AgentEvent {
+            kind: PrintCommand::Issue,
+            position: SUPERVISOR_POSITION.to_string(),
+            message: "Stopping after the current step, press Ctrl-C again to exit".to_string(),
+        });
+
+        if tokio::signal::ctrl_c().await.is_ok() {
+            process::exit(130);
+        }
+    });
+}
+
+fn kill_group(pgid: i32) {
+    unsafe {
+        libc::killpg(pgid, libc::SIGKILL);
+    }
+}
+
+// Checks for an exit without reaping, so the process id stays reserved until `reap`.
+fn has_exited(pid: i32) -> io::Result<bool> {
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+    let result: libc::c_int = unsafe {
+        libc::waitid(
+            libc::P_PID,
+            pid as libc::id_t,
+            &mut info,
+            libc::WEXITED | libc::WNOHANG | libc::WNOWAIT,
+        )
+    };
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(unsafe { info.si_pid() } != 0)
+}
+
+fn reap(pid: i32) -> io::Result<(ExitStatus, ResourceUsage)> {
+    let mut status: libc::c_int = 0;
+    let mut rusage: libc::rusage = unsafe { std::mem::zeroed() };
+    loop {
+        if unsafe { libc::wait4(pid, &mut status, 0, &mut rusage) } != -1 {
+            return Ok((
+                ExitStatus::from_raw(status),
+                ResourceUsage::from_rusage(&rusage),
+            ));
+        }
+        let err: io::Error = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_log_dir(name: &str) -> PathBuf {
+        let dir: PathBuf = env::temp_dir().join(format!("supervisor_{}_{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    // Zombies waiting for init to reap them count as gone.
+    fn is_running(pid: i32) -> bool {
+        match fs::read_to_string(format!("/proc/{}/stat", pid)) {
+            Ok(stat) => !stat
+                .rsplit_once(')')
+                .map(|(_, rest)| rest.trim_start().starts_with('Z'))
+                .unwrap_or(false),
+            Err(_) => false,
+        }
+    }
+
+    #[tokio::test]
+    async fn tests_run_captures_streams_and_exit_status() {
+        let log_dir: PathBuf = temp_log_dir("streams");
+        let mut supervisor: ProcessSupervisor = ProcessSupervisor::new(&log_dir);
+
+        let report: ProcessReport = supervisor
+            .run(
+                "shell",
+                Command::new("sh").args(["-c", "echo out; echo err >&2; exit 3"]),
+                Duration::from_secs(10),
+            )
+            .await
+            .unwrap();
+
+        assert!(!report.success());
+        assert!(!report.timed_out);
+        assert_eq!(report.status.code(), Some(3));
+        assert_eq!(report.stdout(), "out\n");
+        assert_eq!(report.stderr(), "err\n");
+        assert_eq!(report.stderr_log, log_dir.join("01-shell.stderr.log"));
+        assert!(report.usage.max_rss_kib > 0);
+
+        let resumed: ProcessSupervisor = ProcessSupervisor::new(&log_dir);
+        assert_eq!(resumed.next_run, 2);
+
+        let _ = fs::remove_dir_all(&log_dir);
+    }
+
+    #[tokio::test]
+    async fn tests_timeout_and_drop_kill_the_whole_group() {
+        let log_dir: PathBuf = temp_log_dir("groups");
+        let mut supervisor: ProcessSupervisor = ProcessSupervisor::new(&log_dir);
+
+        let report: ProcessReport = supervisor
+            .run(
+                "timeout",
+                Command::new("sh").args(["-c", "sleep 30 & echo $!; wait"]),
+                Duration::from_millis(500),
+            )
+            .await
+            .unwrap();
+        assert!(report.timed_out);
+        assert!(!report.success());
+
+        let grandchild: i32 = fs::read_to_string(&report.stdout_log)
+            .unwrap()
+            .trim()
+            .parse()
+            .unwrap();
+        time::sleep(Duration::from_millis(200)).await;
+        assert!(!is_running(grandchild));
+
+        let process: SupervisedProcess = supervisor
+            .spawn(
+                "dropped",
+                Command::new("sleep").arg("30"),
+                Duration::from_secs(60),
+            )
+            .unwrap();
+        let pid: i32 = process.id() as i32;
+        assert!(is_running(pid));
+        drop(process);
+        assert!(!is_running(pid));
+        assert!(!LIVE_GROUPS.lock().unwrap().contains(&pid));
+
+        let _ = fs::remove_dir_all(&log_dir);
+    }
+}
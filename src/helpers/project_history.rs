@@ -0,0 +1,230 @@
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StepOutcome {
+    NotBuilt,
+    BuildOk,
+    CompileErrors,
+    LintErrors,
+    ContractMismatch,
+    RuntimeErrors,
+}
+
+impl StepOutcome {
+    const ALL: [StepOutcome; 6] = [
+        Self::NotBuilt,
+        Self::BuildOk,
+        Self::CompileErrors,
+        Self::LintErrors,
+        Self::ContractMismatch,
+        Self::RuntimeErrors,
+    ];
+
+    pub fn compiles(&self) -> bool {
+        !matches!(self, Self::NotBuilt | Self::CompileErrors)
+    }
+}
+
+impl fmt::Display for StepOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let outcome: &str = match self {
+            Self::NotBuilt => "not built",
+            Self::BuildOk => "build ok",
+            Self::CompileErrors => "compile errors",
+            Self::LintErrors => "lint errors",
+            Self::ContractMismatch => "contract mismatch",
+            Self::RuntimeErrors => "runtime errors",
+        };
+        write!(f, "{}", outcome)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectHistory {
+    root: PathBuf,
+}
+
+impl ProjectHistory {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+        }
+    }
+
+    pub fn init(root: &Path) -> io::Result<Self> {
+        let history: ProjectHistory = Self::new(root);
+        history.git(&["init", "--quiet"])?;
+        history.git(&["add", "--all"])?;
+        history.git(&[
+            "commit",
+            "--quiet",
+            "--allow-empty",
+            "-m",
+            "Scaffold project",
+        ])?;
+        Ok(history)
+    }
+
+    pub fn commit_step(
+        &self,
+        agent_position: &str,
+        ai_function: &str,
+        outcome: StepOutcome,
+    ) -> io::Result<()> {
+        let message: String = format!("{}: {} ({})", agent_position, ai_function, outcome);
+        self.git(&["add", "--all"])?;
+        self.git(&["commit", "--quiet", "--allow-empty", "-m", &message])?;
+        Ok(())
+    }
+
+    // The newest step whose code compiled, read back from the commit messages.
+    pub fn last_good_revision(&self) -> io::Result<Option<String>> {
+        let log: Output = self.git(&["log", "--format=%H %s"])?;
+        let log: String = String::from_utf8_lossy(&log.stdout).to_string();
+
+        Ok(log.lines().find_map(|line| {
+            let (revision, subject) = line.split_once(' ')?;
+            StepOutcome::ALL
+                .iter()
+                .any(|outcome| outcome.compiles() && subject.ends_with(&format!("({})", outcome)))
+                .then(|| revision.to_string())
+        }))
+    }
+
+    // Puts the files back as they were at `revision`, removing files added since, and records
+    // that as a step of its own.
+    pub fn restore_revision(&self, revision: &str) -> io::Result<()> {
+        self.git(&[
+            "restore",
+            "--source",
+            revision,
+            "--staged",
+            "--worktree",
+            "--",
+            ".",
+        ])?;
+        let message: String = format!("Roll back to {}", revision);
+        self.git(&["commit", "--quiet", "--allow-empty", "-m", &message])?;
+        Ok(())
+    }
+
+    fn git(&self, args: &[&str]) -> io::Result<Output> {
+        let output: Output = Command::new("git")
+            .args([
+                "-c",
+                "user.name=basic_auto_gpt",
+                "-c",
+                "user.email=basic_auto_gpt@localhost",
+                "-c",
+                "commit.gpgsign=false",
+            ])
+            .args(args)
+            .current_dir(&self.root)
+            .output()?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::workspace::new_run_id;
+    use std::{env, fs};
+
+    #[test]
+    fn tests_history_records_each_step() {
+        let root: PathBuf = env::temp_dir().join(format!("history-{}", new_run_id()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("main.rs"), "fn main() {}").unwrap();
+
+        let history: ProjectHistory = ProjectHistory::init(&root).unwrap();
+
+        fs::write(root.join("main.rs"), "fn main() { good() }").unwrap();
+        history
+            .commit_step(
+                "Backend Developer",
+                "print_improved_webserver_code",
+                StepOutcome::BuildOk,
+            )
+            .unwrap();
+
+        fs::write(root.join("main.rs"), "fn main() { broken( }").unwrap();
+        history
+            .commit_step(
+                "Backend Developer",
+                "print_fixed_code",
+                StepOutcome::CompileErrors,
+            )
+            .unwrap();
+
+        let log: Output = history.git(&["log", "--format=%s"]).unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&log.stdout).trim(),
+            "Backend Developer: print_fixed_code (compile errors)\n\
+             Backend Developer: print_improved_webserver_code (build ok)\n\
+             Scaffold project"
+        );
+
+        let diff: Output = history.git(&["diff", "HEAD~1", "--", "main.rs"]).unwrap();
+        assert!(String::from_utf8_lossy(&diff.stdout).contains("+fn main() { broken( }"));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn tests_restore_returns_to_last_compiling_step() {
+        let root: PathBuf = env::temp_dir().join(format!("rollback-{}", new_run_id()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("main.rs"), "fn main() {}").unwrap();
+
+        let history: ProjectHistory = ProjectHistory::init(&root).unwrap();
+        assert_eq!(history.last_good_revision().unwrap(), None);
+
+        fs::write(root.join("main.rs"), "fn main() { panic!() }").unwrap();
+        history
+            .commit_step(
+                "Backend Developer",
+                "print_improved_webserver_code",
+                StepOutcome::RuntimeErrors,
+            )
+            .unwrap();
+        let good_revision: String = history.last_good_revision().unwrap().unwrap();
+
+        fs::write(root.join("main.rs"), "fn main() { broken( }").unwrap();
+        fs::write(root.join("extra.rs"), "pub fn extra() {}").unwrap();
+        history
+            .commit_step(
+                "Backend Developer",
+                "print_fixed_code",
+                StepOutcome::CompileErrors,
+            )
+            .unwrap();
+        assert_eq!(
+            history.last_good_revision().unwrap(),
+            Some(good_revision.clone())
+        );
+
+        history.restore_revision(&good_revision).unwrap();
+        assert_eq!(
+            fs::read_to_string(root.join("main.rs")).unwrap(),
+            "fn main() { panic!() }"
+        );
+        assert!(!root.join("extra.rs").exists());
+        assert_eq!(history.last_good_revision().unwrap(), Some(good_revision));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
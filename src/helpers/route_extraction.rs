@@ -0,0 +1,715 @@
+use crate::models::general::{
+    api_schema::{FieldType, HttpMethod, RouteObject},
+    project_files::ProjectFiles,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+use syn::{
+    punctuated::Punctuated,
+    visit::{self, Visit},
+    Attribute, Expr, ExprCall, ExprMethodCall, Fields, FnArg, GenericArgument, ItemFn, ItemStruct,
+    Lit, LitStr, Local, Pat, PathArguments, ReturnType, Token, Type,
+};
+
+const MAX_TYPE_DEPTH: usize = 8;
+
+#[derive(Debug, PartialEq)]
+pub enum ExtractError {
+    Parse { path: String, message: String },
+    NoRoutes,
+}
+
+impl fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse { path, message } => write!(f, "could not parse '{}': {}", path, message),
+            Self::NoRoutes => write!(f, "no actix-web routes found"),
+        }
+    }
+}
+
+impl std::error::Error for ExtractError {}
+
+// Finds the routes of an actix-web server without running it. Understands
+// `.route("/path", web::get().to(handler))`, `web::resource("/path").route(...)`,
+// `web::scope("/prefix")` and `#[get("/path")]` handlers registered with `.service(handler)`.
+// Request bodies come from `web::Json<T>` extractors, responses from `Json<T>` return types
+// or the value passed to `.json(...)`.
+pub fn extract_routes(files: &ProjectFiles) -> Result<Vec<RouteObject>, ExtractError> {
+    let mut parsed_files: Vec<syn::File> = vec![];
+    for (path, contents) in files.iter() {
+        let parsed: syn::File = syn::parse_file(contents).map_err(|e| ExtractError::Parse {
+            path: path.clone(),
+            message: e.to_string(),
+        })?;
+        parsed_files.push(parsed);
+    }
+
+    let mut index: SourceIndex = SourceIndex::default();
+    let mut collector: RouteCollector = RouteCollector::default();
+    for parsed in &parsed_files {
+        index.visit_file(parsed);
+        collector.visit_file(parsed);
+    }
+
+    let mut registrations: Vec<(HttpMethod, String, String)> = collector.routes;
+    for (handler_name, handler) in &index.handlers {
+        if let Some((method, path)) = attribute_route(&handler.attrs) {
+            let prefix: String = collector
+                .services
+                .get(handler_name)
+                .cloned()
+                .unwrap_or_default();
+            registrations.push((method, join_path(&prefix, &path), handler_name.clone()));
+        }
+    }
+
+    let mut routes: Vec<RouteObject> = registrations
+        .into_iter()
+        .map(|(method, path, handler_name)| {
+            let (request_body, response): (Option<FieldType>, Option<FieldType>) =
+                match index.handlers.get(&handler_name) {
+                    Some(handler) => (index.request_body(handler), index.response(handler)),
+                    None => (None, Some(FieldType::Any)),
+                };
+            RouteObject::new(method, &path, request_body, response)
+        })
+        .collect();
+
+    routes.sort_by(|a, b| (&a.route, a.method).cmp(&(&b.route, b.method)));
+    routes.dedup_by(|a, b| a.route == b.route && a.method == b.method);
+
+    if routes.is_empty() {
+        return Err(ExtractError::NoRoutes);
+    }
+    Ok(routes)
+}
+
+#[derive(Default)]
+struct SourceIndex<'ast> {
+    structs: HashMap<String, &'ast ItemStruct>,
+    handlers: HashMap<String, &'ast ItemFn>,
+}
+
+impl<'ast> Visit<'ast> for SourceIndex<'ast> {
+    fn visit_item_struct(&mut self, item: &'ast ItemStruct) {
+        self.structs.entry(item.ident.to_string()).or_insert(item);
+    }
+
+    fn visit_item_fn(&mut self, item: &'ast ItemFn) {
+        self.handlers
+            .entry(item.sig.ident.to_string())
+            .or_insert(item);
+        visit::visit_item_fn(self, item);
+    }
+}
+
+impl SourceIndex<'_> {
+    fn request_body(&self, handler: &ItemFn) -> Option<FieldType> {
+        handler.sig.inputs.iter().find_map(|input| match input {
+            FnArg::Typed(arg) => {
+                extractor_inner(&arg.ty, "Json").map(|inner| self.field_type(inner, 0))
+            }
+            FnArg::Receiver(_) => None,
+        })
+    }
+
+    fn response(&self, handler: &ItemFn) -> Option<FieldType> {
+        if let ReturnType::Type(_, return_type) = &handler.sig.output {
+            let success_type: &Type = extractor_inner(return_type, "Result").unwrap_or(return_type);
+            if let Some(inner) = extractor_inner(success_type, "Json") {
+                return Some(self.field_type(inner, 0));
+            }
+        }
+
+        let mut bodies: ResponseBodies = ResponseBodies::default();
+        for input in &handler.sig.inputs {
+            if let FnArg::Typed(arg) = input {
+                if let Pat::Ident(name) = arg.pat.as_ref() {
+                    let ty: &Type = extractor_inner(&arg.ty, "Json").unwrap_or(&arg.ty);
+                    bodies
+                        .typed_bindings
+                        .insert(name.ident.to_string(), ty.clone());
+                }
+            }
+        }
+        bodies.visit_block(&handler.block);
+
+        match bodies.json_values.first() {
+            Some(value) => Some(
+                bodies
+                    .binding_type(value)
+                    .map(|ty| self.field_type(&ty, 0))
+                    .unwrap_or(FieldType::Any),
+            ),
+            None if bodies.has_text_body => Some(FieldType::String),
+            None => None,
+        }
+    }
+
+    fn field_type(&self, ty: &Type, depth: usize) -> FieldType {
+        if depth > MAX_TYPE_DEPTH {
+            return FieldType::Any;
+        }
+
+        match ty {
+            Type::Reference(reference) => self.field_type(&reference.elem, depth),
+            Type::Paren(paren) => self.field_type(&paren.elem, depth),
+            Type::Slice(slice) => {
+                FieldType::Array(Box::new(self.field_type(&slice.elem, depth + 1)))
+            }
+            Type::Array(array) => {
+                FieldType::Array(Box::new(self.field_type(&array.elem, depth + 1)))
+            }
+            Type::Path(type_path) => {
+                let Some(segment) = type_path.path.segments.last() else {
+                    return FieldType::Any;
+                };
+                let first_argument = || first_type_argument(&segment.arguments);
+
+                match segment.ident.to_string().as_str() {
+                    "String" | "str" | "char" | "Uuid" | "NaiveDate" | "NaiveDateTime"
+                    | "DateTime" => FieldType::String,
+                    "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32"
+                    | "i64" | "i128" | "isize" | "f32" | "f64" => FieldType::Number,
+                    "bool" => FieldType::Bool,
+                    "Option" => FieldType::Optional(Box::new(
+                        first_argument()
+                            .map(|inner| self.field_type(inner, depth + 1))
+                            .unwrap_or(FieldType::Any),
+                    )),
+                    "Vec" | "VecDeque" | "HashSet" | "BTreeSet" => FieldType::Array(Box::new(
+                        first_argument()
+                            .map(|inner| self.field_type(inner, depth + 1))
+                            .unwrap_or(FieldType::Any),
+                    )),
+                    "Box" | "Arc" | "Rc" | "Json" => first_argument()
+                        .map(|inner| self.field_type(inner, depth))
+                        .unwrap_or(FieldType::Any),
+                    "HashMap" | "BTreeMap" => FieldType::Object(BTreeMap::new()),
+                    name => match self.structs.get(name) {
+                        Some(item) => self.struct_type(item, depth + 1),
+                        None => FieldType::Any,
+                    },
+                }
+            }
+            _ => FieldType::Any,
+        }
+    }
+
+    fn struct_type(&self, item: &ItemStruct, depth: usize) -> FieldType {
+        let Fields::Named(named) = &item.fields else {
+            return FieldType::Any;
+        };
+        let rename_all: Option<String> = serde_options(&item.attrs).rename_all;
+
+        let mut fields: BTreeMap<String, FieldType> = BTreeMap::new();
+        for field in &named.named {
+            let options: SerdeOptions = serde_options(&field.attrs);
+            if options.skip {
+                continue;
+            }
+            let Some(ident) = &field.ident else {
+                continue;
+            };
+
+            let field_type: FieldType = self.field_type(&field.ty, depth);
+            if options.flatten {
+                if let FieldType::Object(flattened) = field_type {
+                    fields.extend(flattened);
+                }
+                continue;
+            }
+
+            let name: String = options.rename.unwrap_or_else(|| {
+                let name: String = ident.to_string();
+                let name: &str = name.strip_prefix("r#").unwrap_or(&name);
+                rename_field(name, rename_all.as_deref())
+            });
+            fields.insert(name, field_type);
+        }
+        FieldType::Object(fields)
+    }
+}
+
+#[derive(Default)]
+struct RouteCollector {
+    // (method, full path, handler name)
+    routes: Vec<(HttpMethod, String, String)>,
+    // handler name -> scope prefix it was registered under with `.service(handler)`
+    services: HashMap<String, String>,
+    prefixes: Vec<String>,
+}
+
+impl RouteCollector {
+    fn prefix(&self) -> String {
+        self.prefixes.concat()
+    }
+}
+
+impl<'ast> Visit<'ast> for RouteCollector {
+    fn visit_expr_method_call(&mut self, call: &'ast ExprMethodCall) {
+        let chain_scope: Option<String> = chain_root_literal(&call.receiver, "scope");
+        let scoped_prefix: String = format!("{}{}", self.prefix(), chain_scope.unwrap_or_default());
+
+        match (call.method.to_string().as_str(), call.args.len()) {
+            ("route", 2) => {
+                let mut args = call.args.iter();
+                if let (Some(path), Some((method, handler))) = (
+                    args.next().and_then(string_literal),
+                    args.next().and_then(method_handler),
+                ) {
+                    self.routes
+                        .push((method, join_path(&scoped_prefix, &path), handler));
+                }
+            }
+            ("route", 1) => {
+                if let (Some(path), Some((method, handler))) = (
+                    chain_root_literal(&call.receiver, "resource"),
+                    call.args.first().and_then(method_handler),
+                ) {
+                    self.routes
+                        .push((method, join_path(&scoped_prefix, &path), handler));
+                }
+            }
+            ("service", 1) => {
+                if let Some(Expr::Path(handler)) = call.args.first() {
+                    if let Some(segment) = handler.path.segments.last() {
+                        self.services
+                            .insert(segment.ident.to_string(), scoped_prefix.clone());
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        self.visit_expr(&call.receiver);
+        let pushed: bool = match chain_root_literal(&call.receiver, "scope") {
+            Some(scope) => {
+                self.prefixes.push(scope);
+                true
+            }
+            None => false,
+        };
+        for arg in &call.args {
+            self.visit_expr(arg);
+        }
+        if pushed {
+            self.prefixes.pop();
+        }
+    }
+}
+
+#[derive(Default)]
+struct ResponseBodies {
+    typed_bindings: HashMap<String, Type>,
+    json_values: Vec<Expr>,
+    has_text_body: bool,
+}
+
+impl ResponseBodies {
+    fn binding_type(&self, value: &Expr) -> Option<Type> {
+        match value {
+            Expr::Reference(reference) => self.binding_type(&reference.expr),
+            Expr::Paren(paren) => self.binding_type(&paren.expr),
+            Expr::Path(path) => path
+                .path
+                .get_ident()
+                .and_then(|ident| self.typed_bindings.get(&ident.to_string()))
+                .cloned(),
+            _ => None,
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for ResponseBodies {
+    fn visit_local(&mut self, local: &'ast Local) {
+        if let Pat::Type(typed) = &local.pat {
+            if let Pat::Ident(name) = typed.pat.as_ref() {
+                self.typed_bindings
+                    .insert(name.ident.to_string(), (*typed.ty).clone());
+            }
+        }
+        visit::visit_local(self, local);
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast ExprMethodCall) {
+        match call.method.to_string().as_str() {
+            "json" => self.json_values.extend(call.args.first().cloned()),
+            "body" => self.has_text_body = true,
+            _ => {}
+        }
+        visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast ExprCall) {
+        if let Expr::Path(func) = call.func.as_ref() {
+            if func
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "Json")
+            {
+                self.json_values.extend(call.args.first().cloned());
+            }
+        }
+        visit::visit_expr_call(self, call);
+    }
+}
+
+#[derive(Default)]
+struct SerdeOptions {
+    rename: Option<String>,
+    rename_all: Option<String>,
+    skip: bool,
+    flatten: bool,
+}
+
+fn serde_options(attrs: &[Attribute]) -> SerdeOptions {
+    let mut options: SerdeOptions = SerdeOptions::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        // Unknown options are skipped; a parse error only stops reading this attribute.
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") && meta.input.peek(Token![=]) {
+                options.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("rename_all") && meta.input.peek(Token![=]) {
+                options.rename_all = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("skip") {
+                options.skip = true;
+            } else if meta.path.is_ident("flatten") {
+                options.flatten = true;
+            } else if meta.input.peek(Token![=]) {
+                meta.value()?.parse::<Expr>()?;
+            } else if meta.input.peek(syn::token::Paren) {
+                meta.parse_nested_meta(|nested| {
+                    if nested.input.peek(Token![=]) {
+                        nested.value()?.parse::<Expr>()?;
+                    }
+                    Ok(())
+                })?;
+            }
+            Ok(())
+        });
+    }
+    options
+}
+
+fn rename_field(name: &str, rename_all: Option<&str>) -> String {
+    let words: Vec<&str> = name.split('_').filter(|word| !word.is_empty()).collect();
+    let capitalize = |word: &str| -> String {
+        let mut chars = word.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    };
+
+    match rename_all {
+        Some("lowercase") => name.to_lowercase(),
+        Some("UPPERCASE") => name.to_uppercase(),
+        Some("SCREAMING_SNAKE_CASE") => name.to_uppercase(),
+        Some("kebab-case") => words.join("-"),
+        Some("SCREAMING-KEBAB-CASE") => words.join("-").to_uppercase(),
+        Some("PascalCase") => words.iter().map(|word| capitalize(word)).collect(),
+        Some("camelCase") => words
+            .iter()
+            .enumerate()
+            .map(|(i, word)| {
+                if i == 0 {
+                    word.to_string()
+                } else {
+                    capitalize(word)
+                }
+            })
+            .collect(),
+        _ => name.to_string(),
+    }
+}
+
+// `#[get("/path")]` or `#[actix_web::get("/path")]`
+fn attribute_route(attrs: &[Attribute]) -> Option<(HttpMethod, String)> {
+    attrs.iter().find_map(|attr| {
+        let method: HttpMethod = attr
+            .path()
+            .segments
+            .last()?
+            .ident
+            .to_string()
+            .parse()
+            .ok()?;
+        let args: Punctuated<Expr, Token![,]> =
+            attr.parse_args_with(Punctuated::parse_terminated).ok()?;
+        Some((method, string_literal(args.first()?)?))
+    })
+}
+
+// `web::get().to(handler)`, possibly with guards or other calls in between.
+fn method_handler(expr: &Expr) -> Option<(HttpMethod, String)> {
+    let mut handler: Option<String> = None;
+    let mut current: &Expr = expr;
+
+    while let Expr::MethodCall(call) = current {
+        if call.method == "to" {
+            if let Some(Expr::Path(path)) = call.args.first() {
+                handler = path
+                    .path
+                    .segments
+                    .last()
+                    .map(|segment| segment.ident.to_string());
+            }
+        }
+        current = &call.receiver;
+    }
+
+    let Expr::Call(root) = current else {
+        return None;
+    };
+    let Expr::Path(func) = root.func.as_ref() else {
+        return None;
+    };
+    let method: HttpMethod = func.path.segments.last()?.ident.to_string().parse().ok()?;
+    Some((method, handler?))
+}
+
+// Returns "/p" for chains starting with `web::<function>("/p")`.
+fn chain_root_literal(expr: &Expr, function: &str) -> Option<String> {
+    let mut current: &Expr = expr;
+    while let Expr::MethodCall(call) = current {
+        current = &call.receiver;
+    }
+
+    let Expr::Call(root) = current else {
+        return None;
+    };
+    let Expr::Path(func) = root.func.as_ref() else {
+        return None;
+    };
+    if func.path.segments.last()?.ident != function {
+        return None;
+    }
+    string_literal(root.args.first()?)
+}
+
+fn string_literal(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Lit(literal) => match &literal.lit {
+            Lit::Str(value) => Some(value.value()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Returns `T` for `Json<T>`, `web::Json<T>`, `Result<T, E>` and so on.
+fn extractor_inner<'a>(ty: &'a Type, extractor: &str) -> Option<&'a Type> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != extractor {
+        return None;
+    }
+    first_type_argument(&segment.arguments)
+}
+
+fn first_type_argument(arguments: &PathArguments) -> Option<&Type> {
+    let PathArguments::AngleBracketed(generics) = arguments else {
+        return None;
+    };
+    generics.args.iter().find_map(|argument| match argument {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    })
+}
+
+fn join_path(prefix: &str, path: &str) -> String {
+    let joined: String = format!(
+        "{}/{}",
+        prefix.trim_end_matches('/'),
+        path.trim_start_matches('/')
+    );
+    if joined.len() > 1 {
+        joined.trim_end_matches('/').to_string()
+    } else {
+        joined
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::general::project_files::MAIN_FILE;
+
+    fn project(files: &[(&str, &str)]) -> ProjectFiles {
+        let mut project: ProjectFiles = ProjectFiles::default();
+        for (path, contents) in files {
+            project.insert(path, contents.to_string()).unwrap();
+        }
+        project
+    }
+
+    fn methods_and_routes(routes: &[RouteObject]) -> Vec<(HttpMethod, &str)> {
+        routes
+            .iter()
+            .map(|route| (route.method, route.route.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn tests_routes_are_extracted_from_code_template() {
+        let files: ProjectFiles = project(&[(
+            MAIN_FILE,
+            include_str!("../../web_gpt_template/src/code_template.rs"),
+        )]);
+        let routes: Vec<RouteObject> = extract_routes(&files).unwrap();
+
+        assert_eq!(
+            methods_and_routes(&routes),
+            vec![
+                (HttpMethod::Post, "/login"),
+                (HttpMethod::Post, "/register"),
+                (HttpMethod::Get, "/task"),
+                (HttpMethod::Post, "/task"),
+                (HttpMethod::Put, "/task"),
+                (HttpMethod::Get, "/task/{id}"),
+                (HttpMethod::Delete, "/task/{id}"),
+            ]
+        );
+
+        let create_task: &RouteObject = &routes[3];
+        assert_eq!(
+            create_task.request_body,
+            Some(FieldType::Object(BTreeMap::from([
+                ("completed".to_string(), FieldType::Bool),
+                ("id".to_string(), FieldType::Number),
+                ("name".to_string(), FieldType::String),
+            ])))
+        );
+        assert_eq!(create_task.response, None);
+        assert_eq!(routes[5].response, Some(FieldType::Any));
+        assert_eq!(routes[0].response, Some(FieldType::String));
+        assert!(routes[5].is_route_dynamic);
+    }
+
+    #[test]
+    fn tests_routes_are_extracted_across_modules_scopes_and_attributes() {
+        let main: &str = r#"
+            mod handlers;
+            mod models;
+            use actix_web::{web, App, HttpServer};
+
+            #[actix_web::main]
+            async fn main() -> std::io::Result<()> {
+                HttpServer::new(|| {
+                    App::new()
+                        .service(
+                            web::scope("/api")
+                                .service(handlers::list_coins)
+                                .service(web::resource("/coins/{symbol}").route(web::delete().to(handlers::remove_coin)))
+                                .route("/coins/", web::post().to(handlers::add_coin)),
+                        )
+                        .service(handlers::health)
+                })
+                .bind(("127.0.0.1", 8080))?
+                .run()
+                .await
+            }
+        "#;
+        let handlers: &str = r#"
+            use actix_web::{get, web, HttpResponse, Responder};
+            use crate::models::{Coin, NewCoin};
+
+            #[get("/coins")]
+            pub async fn list_coins() -> web::Json<Vec<Coin>> {
+                web::Json(vec![])
+            }
+
+            pub async fn add_coin(coin: web::Json<NewCoin>) -> impl Responder {
+                let created: Coin = Coin { symbol: coin.symbol.clone(), price_usd: 0.0, tags: None };
+                HttpResponse::Created().json(&created)
+            }
+
+            pub async fn remove_coin(symbol: web::Path<String>) -> impl Responder {
+                HttpResponse::NoContent().finish()
+            }
+
+            #[actix_web::get("/health")]
+            pub async fn health() -> impl Responder {
+                HttpResponse::Ok().body("ok")
+            }
+        "#;
+        let models: &str = r#"
+            use serde::{Deserialize, Serialize};
+
+            #[derive(Serialize, Deserialize)]
+            #[serde(rename_all = "camelCase")]
+            pub struct Coin {
+                pub symbol: String,
+                pub price_usd: f64,
+                pub tags: Option<Vec<String>>,
+            }
+
+            #[derive(Deserialize)]
+            pub struct NewCoin {
+                #[serde(rename = "ticker")]
+                pub symbol: String,
+                #[serde(skip)]
+                pub internal: u8,
+            }
+        "#;
+        let files: ProjectFiles = project(&[
+            (MAIN_FILE, main),
+            ("src/handlers.rs", handlers),
+            ("src/models.rs", models),
+        ]);
+
+        let routes: Vec<RouteObject> = extract_routes(&files).unwrap();
+        assert_eq!(
+            methods_and_routes(&routes),
+            vec![
+                (HttpMethod::Get, "/api/coins"),
+                (HttpMethod::Post, "/api/coins"),
+                (HttpMethod::Delete, "/api/coins/{symbol}"),
+                (HttpMethod::Get, "/health"),
+            ]
+        );
+
+        let coin: FieldType = FieldType::Object(BTreeMap::from([
+            ("priceUsd".to_string(), FieldType::Number),
+            ("symbol".to_string(), FieldType::String),
+            (
+                "tags".to_string(),
+                FieldType::Optional(Box::new(FieldType::Array(Box::new(FieldType::String)))),
+            ),
+        ]));
+        assert_eq!(
+            routes[0].response,
+            Some(FieldType::Array(Box::new(coin.clone())))
+        );
+        assert_eq!(
+            routes[1].request_body,
+            Some(FieldType::Object(BTreeMap::from([(
+                "ticker".to_string(),
+                FieldType::String
+            )])))
+        );
+        assert_eq!(routes[1].response, Some(coin));
+        assert_eq!(routes[2].path_params, vec!["symbol".to_string()]);
+        assert_eq!(routes[2].response, None);
+        assert_eq!(routes[3].response, Some(FieldType::String));
+    }
+
+    #[test]
+    fn tests_unparsable_or_routeless_code_is_reported() {
+        let broken: ProjectFiles = project(&[(MAIN_FILE, "fn main( {")]);
+        assert!(matches!(
+            extract_routes(&broken),
+            Err(ExtractError::Parse { path, .. }) if path == MAIN_FILE
+        ));
+
+        let routeless: ProjectFiles = project(&[(MAIN_FILE, "fn main() {}")]);
+        assert_eq!(extract_routes(&routeless), Err(ExtractError::NoRoutes));
+    }
+}
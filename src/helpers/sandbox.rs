@@ -0,0 +1,415 @@
+use std::{
+    ffi::{CStr, CString},
+    fs, io, mem,
+    os::unix::{ffi::OsStrExt, process::CommandExt},
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus},
+};
+
+use super::{loopback_relay, test_bench::SandboxLimits};
+
+const MOUNT_ATTR_RDONLY: u64 = 0x1;
+const SECCOMP_DATA_NR: u32 = 0;
+const SECCOMP_DATA_ARCH: u32 = 4;
+const SECCOMP_DATA_ARG0: u32 = 16;
+
+#[cfg(target_arch = "x86_64")]
+const AUDIT_ARCH: Option<u32> = Some(0xC000_003E);
+#[cfg(target_arch = "aarch64")]
+const AUDIT_ARCH: Option<u32> = Some(0xC000_00B7);
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+const AUDIT_ARCH: Option<u32> = None;
+
+// Nothing a build script or web server needs, and enough to undo the sandbox or reach outside it.
+const DENIED_SYSCALLS: [libc::c_long; 29] = [
+    libc::SYS_mount,
+    libc::SYS_umount2,
+    libc::SYS_mount_setattr,
+    libc::SYS_open_tree,
+    libc::SYS_move_mount,
+    libc::SYS_fsopen,
+    libc::SYS_fsconfig,
+    libc::SYS_fsmount,
+    libc::SYS_fspick,
+    libc::SYS_pivot_root,
+    libc::SYS_chroot,
+    libc::SYS_unshare,
+    libc::SYS_setns,
+    libc::SYS_ptrace,
+    libc::SYS_process_vm_readv,
+    libc::SYS_process_vm_writev,
+    libc::SYS_kexec_load,
+    libc::SYS_init_module,
+    libc::SYS_finit_module,
+    libc::SYS_delete_module,
+    libc::SYS_reboot,
+    libc::SYS_swapon,
+    libc::SYS_swapoff,
+    libc::SYS_bpf,
+    libc::SYS_perf_event_open,
+    libc::SYS_keyctl,
+    libc::SYS_add_key,
+    libc::SYS_request_key,
+    libc::SYS_userfaultfd,
+];
+
+const NAMESPACE_FLAGS: libc::c_int = libc::CLONE_NEWUSER
+    | libc::CLONE_NEWNS
+    | libc::CLONE_NEWNET
+    | libc::CLONE_NEWPID
+    | libc::CLONE_NEWIPC
+    | libc::CLONE_NEWUTS
+    | libc::CLONE_NEWCGROUP;
+
+// Runs commands in fresh user, mount and network namespaces: the whole filesystem is read-only
+// except `writable_dir`, the only network device is loopback, resources are capped with rlimits
+// and a seccomp filter keeps the process from leaving or reshaping the sandbox.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sandbox {
+    writable_dir: PathBuf,
+    relay_socket: PathBuf,
+    limits: SandboxLimits,
+}
+
+impl Sandbox {
+    pub fn new(writable_dir: &Path, relay_socket: &Path, limits: SandboxLimits) -> Self {
+        Self {
+            writable_dir: writable_dir.to_path_buf(),
+            relay_socket: relay_socket.to_path_buf(),
+            limits,
+        }
+    }
+
+    pub fn relay_socket(&self) -> &Path {
+        &self.relay_socket
+    }
+
+    // Kernels can disable unprivileged namespaces, so check by running `true` inside one. A deep
+    // workspace can also leave no room for the relay socket in a Unix socket address.
+    pub fn probe(&self) -> io::Result<()> {
+        loopback_relay::socket_address(&self.relay_socket)?;
+        let mut command: Command = Command::new("true");
+        self.apply(&mut command, None)?;
+        let status: ExitStatus = command.status()?;
+        if !status.success() {
+            return Err(io::Error::other(format!(
+                "sandboxed probe exited with {}",
+                status
+            )));
+        }
+        Ok(())
+    }
+
+    // With a relay port, the sandbox also serves `relay_socket` so the agent can reach a server
+    // listening on that port inside it.
+    pub fn apply(&self, command: &mut Command, relay_port: Option<u16>) -> io::Result<()> {
+        let tmp_dir: PathBuf = self.writable_dir.join("target").join("tmp");
+        fs::create_dir_all(&tmp_dir)?;
+        command.env("TMPDIR", &tmp_dir);
+
+        let relay: Option<(&Path, u16)> =
+            relay_port.map(|port| (self.relay_socket.as_path(), port));
+        let setup: SandboxSetup = SandboxSetup::new(&self.writable_dir, self.limits, relay)?;
+        unsafe {
+            command.pre_exec(move || setup.enter());
+        }
+        Ok(())
+    }
+}
+
+// Everything `enter` needs, prepared before the fork because the child may not allocate.
+struct SandboxSetup {
+    uid_map: Vec<u8>,
+    gid_map: Vec<u8>,
+    writable_dir: CString,
+    limits: SandboxLimits,
+    relay: Option<(libc::sockaddr_un, u16)>,
+    filter: Vec<libc::sock_filter>,
+}
+
+impl SandboxSetup {
+    fn new(
+        writable_dir: &Path,
+        limits: SandboxLimits,
+        relay: Option<(&Path, u16)>,
+    ) -> io::Result<Self> {
+        let audit_arch: u32 = AUDIT_ARCH.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                "seccomp filter is not available on this architecture",
+            )
+        })?;
+        let writable_dir: CString =
+            CString::new(writable_dir.canonicalize()?.as_os_str().as_bytes())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let relay: Option<(libc::sockaddr_un, u16)> = match relay {
+            Some((socket_path, port)) => {
+                let _ = fs::remove_file(socket_path);
+                Some((loopback_relay::socket_address(socket_path)?, port))
+            }
+            None => None,
+        };
+
+        let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+        Ok(Self {
+            uid_map: format!("{} {} 1", uid, uid).into_bytes(),
+            gid_map: format!("{} {} 1", gid, gid).into_bytes(),
+            writable_dir,
+            limits,
+            relay,
+            filter: seccomp_filter(audit_arch),
+        })
+    }
+
+    // Runs in the child between fork and exec, so it only makes raw system calls.
+    fn enter(&self) -> io::Result<()> {
+        unsafe {
+            check(libc::unshare(
+                libc::CLONE_NEWUSER | libc::CLONE_NEWNS | libc::CLONE_NEWNET,
+            ))?;
+            write_proc_file(c"/proc/self/setgroups", b"deny")?;
+            write_proc_file(c"/proc/self/uid_map", &self.uid_map)?;
+            write_proc_file(c"/proc/self/gid_map", &self.gid_map)?;
+
+            let root: &CStr = c"/";
+            check(libc::mount(
+                std::ptr::null(),
+                root.as_ptr(),
+                std::ptr::null(),
+                libc::MS_REC | libc::MS_PRIVATE,
+                std::ptr::null(),
+            ))?;
+            check(libc::mount(
+                self.writable_dir.as_ptr(),
+                self.writable_dir.as_ptr(),
+                std::ptr::null(),
+                libc::MS_BIND | libc::MS_REC,
+                std::ptr::null(),
+            ))?;
+            set_read_only(root, true)?;
+            set_read_only(&self.writable_dir, false)?;
+            bring_up_loopback()?;
+
+            set_limit(libc::RLIMIT_CPU, self.limits.cpu_secs)?;
+            set_limit(libc::RLIMIT_AS, self.limits.memory_mb * 1024 * 1024)?;
+            set_limit(libc::RLIMIT_FSIZE, self.limits.file_size_mb * 1024 * 1024)?;
+            set_limit(libc::RLIMIT_CORE, 0)?;
+
+            if let Some((address, port)) = &self.relay {
+                match libc::fork() {
+                    -1 => return Err(io::Error::last_os_error()),
+                    0 => loopback_relay::serve_inside(address, *port),
+                    _ => {}
+                }
+            }
+
+            check(libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0))?;
+            let program: libc::sock_fprog = libc::sock_fprog {
+                len: self.filter.len() as libc::c_ushort,
+                filter: self.filter.as_ptr() as *mut libc::sock_filter,
+            };
+            check(libc::prctl(
+                libc::PR_SET_SECCOMP,
+                libc::SECCOMP_MODE_FILTER,
+                &program as *const libc::sock_fprog,
+            ))?;
+        }
+        Ok(())
+    }
+}
+
+fn seccomp_filter(audit_arch: u32) -> Vec<libc::sock_filter> {
+    let load = |offset: u32| statement(libc::BPF_LD | libc::BPF_W | libc::BPF_ABS, offset);
+    let deny = || {
+        statement(
+            libc::BPF_RET | libc::BPF_K,
+            libc::SECCOMP_RET_ERRNO | libc::EPERM as u32,
+        )
+    };
+
+    let mut filter: Vec<libc::sock_filter> = vec![
+        load(SECCOMP_DATA_ARCH),
+        jump(libc::BPF_JEQ, audit_arch, 1, 0),
+        statement(libc::BPF_RET | libc::BPF_K, libc::SECCOMP_RET_KILL_PROCESS),
+        load(SECCOMP_DATA_NR),
+    ];
+
+    // x32 system calls share the x86_64 audit arch but use different numbers.
+    if cfg!(target_arch = "x86_64") {
+        filter.push(jump(libc::BPF_JGE, 0x4000_0000, 0, 1));
+        filter.push(deny());
+    }
+    for syscall in DENIED_SYSCALLS {
+        filter.push(jump(libc::BPF_JEQ, syscall as u32, 0, 1));
+        filter.push(deny());
+    }
+
+    // clone3 takes its flags through a pointer the filter cannot read, so make the C library
+    // fall back to clone and refuse new namespaces there.
+    filter.push(jump(libc::BPF_JEQ, libc::SYS_clone3 as u32, 0, 1));
+    filter.push(statement(
+        libc::BPF_RET | libc::BPF_K,
+        libc::SECCOMP_RET_ERRNO | libc::ENOSYS as u32,
+    ));
+    filter.push(jump(libc::BPF_JEQ, libc::SYS_clone as u32, 0, 3));
+    filter.push(load(SECCOMP_DATA_ARG0));
+    filter.push(jump(libc::BPF_JSET, NAMESPACE_FLAGS as u32, 0, 1));
+    filter.push(deny());
+    filter.push(statement(
+        libc::BPF_RET | libc::BPF_K,
+        libc::SECCOMP_RET_ALLOW,
+    ));
+    filter
+}
+
+fn statement(code: u32, k: u32) -> libc::sock_filter {
+    libc::sock_filter {
+        code: code as u16,
+        jt: 0,
+        jf: 0,
+        k,
+    }
+}
+
+fn jump(condition: u32, k: u32, jt: u8, jf: u8) -> libc::sock_filter {
+    libc::sock_filter {
+        code: (libc::BPF_JMP | condition | libc::BPF_K) as u16,
+        jt,
+        jf,
+        k,
+    }
+}
+
+fn check(result: libc::c_int) -> io::Result<()> {
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+unsafe fn write_proc_file(path: &CStr, contents: &[u8]) -> io::Result<()> {
+    let fd: libc::c_int = libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+    check(fd)?;
+    let written: isize = libc::write(fd, contents.as_ptr().cast(), contents.len());
+    libc::close(fd);
+    if written != contents.len() as isize {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+// The mount_setattr layout from linux/mount.h, which libc does not expose yet.
+#[repr(C)]
+struct MountAttr {
+    attr_set: u64,
+    attr_clr: u64,
+    propagation: u64,
+    userns_fd: u64,
+}
+
+unsafe fn set_read_only(path: &CStr, read_only: bool) -> io::Result<()> {
+    let attr: MountAttr = MountAttr {
+        attr_set: if read_only { MOUNT_ATTR_RDONLY } else { 0 },
+        attr_clr: if read_only { 0 } else { MOUNT_ATTR_RDONLY },
+        propagation: 0,
+        userns_fd: 0,
+    };
+    let result: libc::c_long = libc::syscall(
+        libc::SYS_mount_setattr,
+        libc::AT_FDCWD,
+        path.as_ptr(),
+        libc::AT_RECURSIVE,
+        &attr as *const MountAttr,
+        mem::size_of::<MountAttr>(),
+    );
+    check(result as libc::c_int)
+}
+
+unsafe fn bring_up_loopback() -> io::Result<()> {
+    let socket: libc::c_int = libc::socket(libc::AF_INET, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, 0);
+    check(socket)?;
+
+    let mut request: libc::ifreq = mem::zeroed();
+    for (slot, byte) in request.ifr_name.iter_mut().zip(b"lo") {
+        *slot = *byte as libc::c_char;
+    }
+    request.ifr_ifru.ifru_flags =
+        (libc::IFF_UP | libc::IFF_LOOPBACK | libc::IFF_RUNNING) as libc::c_short;
+    let result: libc::c_int = libc::ioctl(socket, libc::SIOCSIFFLAGS as _, &request);
+    libc::close(socket);
+    check(result)
+}
+
+// glibc gives the resource its own type where musl uses a plain int.
+#[cfg(any(target_env = "gnu", target_env = "uclibc"))]
+type RlimitResource = libc::__rlimit_resource_t;
+#[cfg(not(any(target_env = "gnu", target_env = "uclibc")))]
+type RlimitResource = libc::c_int;
+
+// Never asks for more than the current hard limit, which would need privileges.
+unsafe fn set_limit(resource: RlimitResource, value: u64) -> io::Result<()> {
+    let mut limit: libc::rlimit = mem::zeroed();
+    check(libc::getrlimit(resource, &mut limit))?;
+    limit.rlim_max = limit.rlim_max.min(value);
+    limit.rlim_cur = limit.rlim_max;
+    check(libc::setrlimit(resource, &limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        env,
+        process::{self, Output},
+    };
+
+    #[test]
+    fn tests_sandbox_confines_filesystem_network_and_syscalls() {
+        let writable_dir: PathBuf = env::temp_dir().join(format!("sandbox_{}", process::id()));
+        fs::create_dir_all(&writable_dir).unwrap();
+        let sandbox: Sandbox = Sandbox::new(
+            &writable_dir,
+            &writable_dir.join("server.sock"),
+            SandboxLimits::from_env(),
+        );
+        if let Err(e) = sandbox.probe() {
+            eprintln!("skipping, namespaces are unavailable: {}", e);
+            return;
+        }
+
+        let outside: PathBuf = env::temp_dir().join(format!("outside_{}", process::id()));
+        let script: String = format!(
+            "touch {inside} && echo inside-writable; \
+            touch {outside} 2>/dev/null || echo outside-read-only; \
+            grep -c : /proc/net/dev; \
+            unshare -U true 2>/dev/null || echo unshare-denied; \
+            ulimit -c",
+            inside = writable_dir.join("created").display(),
+            outside = outside.display(),
+        );
+        let mut command: Command = Command::new("sh");
+        command.args(["-c", &script]);
+        sandbox.apply(&mut command, None).unwrap();
+        let output: Output = command.output().unwrap();
+        let _ = fs::remove_dir_all(&writable_dir);
+
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "inside-writable\noutside-read-only\n1\nunshare-denied\n0\n"
+        );
+        assert!(!outside.exists());
+    }
+
+    #[test]
+    fn tests_probe_rejects_relay_socket_path_too_long_to_bind() {
+        let writable_dir: PathBuf = env::temp_dir().join("a".repeat(120));
+        let sandbox: Sandbox = Sandbox::new(
+            &writable_dir,
+            &writable_dir.join("server.sock"),
+            SandboxLimits::from_env(),
+        );
+        let error: io::Error = sandbox.probe().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(!writable_dir.exists());
+    }
+}
@@ -0,0 +1,126 @@
+use std::{fs, io};
+
+use super::workspace::Workspace;
+
+pub const GENERATED_PACKAGE_NAME: &str = "web_gpt_server";
+
+#[derive(Debug, PartialEq)]
+pub struct CrateDependency {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub features: &'static [&'static str],
+}
+
+// Keep in sync with the library list in the backend AI function prompts.
+pub const ALLOWED_DEPENDENCIES: &[CrateDependency] = &[
+    CrateDependency {
+        name: "actix-cors",
+        version: "0.7.0",
+        features: &[],
+    },
+    CrateDependency {
+        name: "actix-web",
+        version: "4.5.1",
+        features: &[],
+    },
+    CrateDependency {
+        name: "async-trait",
+        version: "0.1.77",
+        features: &[],
+    },
+    CrateDependency {
+        name: "reqwest",
+        version: "0.11.26",
+        features: &["json"],
+    },
+    CrateDependency {
+        name: "serde",
+        version: "1.0.197",
+        features: &["derive"],
+    },
+    CrateDependency {
+        name: "serde_json",
+        version: "1.0.114",
+        features: &[],
+    },
+    CrateDependency {
+        name: "tokio",
+        version: "1.36.0",
+        features: &["full"],
+    },
+];
+
+pub fn render_cargo_toml(package_name: &str) -> String {
+    let mut cargo_toml: String = format!(
+        "[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\npublish = false\n\n",
+        package_name
+    );
+
+    // An empty workspace table keeps cargo from attaching the generated crate to a parent workspace.
+    cargo_toml.push_str("[workspace]\n\n[dependencies]\n");
+
+    for dependency in ALLOWED_DEPENDENCIES {
+        if dependency.features.is_empty() {
+            cargo_toml.push_str(&format!(
+                "{} = \"={}\"\n",
+                dependency.name, dependency.version
+            ));
+        } else {
+            let features: Vec<String> = dependency
+                .features
+                .iter()
+                .map(|feature| format!("\"{}\"", feature))
+                .collect();
+            cargo_toml.push_str(&format!(
+                "{} = {{ version = \"={}\", features = [{}] }}\n",
+                dependency.name,
+                dependency.version,
+                features.join(", ")
+            ));
+        }
+    }
+
+    cargo_toml
+}
+
+pub fn render_readme(package_name: &str) -> String {
+    format!(
+        "# {}\n\nWeb server generated by basic_auto_gpt.\n\n```sh\ncargo run\n```\n\nThe server listens on `127.0.0.1:8080`, or on the port in the `PORT` environment variable.\n",
+        package_name
+    )
+}
+
+pub fn scaffold_crate(workspace: &Workspace) -> io::Result<()> {
+    fs::create_dir_all(workspace.root().join("src"))?;
+    fs::write(
+        workspace.root().join("Cargo.toml"),
+        render_cargo_toml(GENERATED_PACKAGE_NAME),
+    )?;
+    fs::write(workspace.root().join(".gitignore"), "/target\n/.auto_gpt\n")?;
+    fs::write(
+        workspace.root().join("README.md"),
+        render_readme(GENERATED_PACKAGE_NAME),
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tests_cargo_toml_pins_allowed_dependencies() {
+        let cargo_toml: String = render_cargo_toml("my_server");
+
+        assert!(cargo_toml.starts_with("[package]\nname = \"my_server\""));
+        assert!(cargo_toml.contains("\n[workspace]\n"));
+        assert!(cargo_toml.contains("actix-web = \"=4.5.1\"\n"));
+        assert!(cargo_toml.contains("actix-cors = \"=0.7.0\"\n"));
+        assert!(cargo_toml.contains("tokio = { version = \"=1.36.0\", features = [\"full\"] }\n"));
+
+        for dependency in ALLOWED_DEPENDENCIES {
+            assert!(cargo_toml.contains(&format!("\n{} = ", dependency.name)));
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use std::{
+    io,
+    net::{Ipv4Addr, SocketAddr, TcpListener},
+    process::ExitStatus,
+    time::{Duration, Instant},
+};
+use tokio::{net::TcpStream, task::JoinHandle, time};
+
+#[cfg(target_os = "linux")]
+use super::loopback_relay;
+use super::process_supervisor::SupervisedProcess;
+#[cfg(target_os = "linux")]
+use std::path::PathBuf;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, PartialEq)]
+pub enum Readiness {
+    Ready,
+    Exited(ExitStatus),
+    TimedOut,
+}
+
+// Where the server can be reached from the agent: directly, or through the loopback relay of
+// the sandbox it runs in. Only Linux has the sandbox.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerAddress {
+    Loopback(u16),
+    #[cfg(target_os = "linux")]
+    Relay(PathBuf),
+}
+
+impl ServerAddress {
+    async fn accepts_connections(&self) -> bool {
+        match self {
+            Self::Loopback(port) => {
+                let address: SocketAddr = SocketAddr::from((Ipv4Addr::LOCALHOST, *port));
+                TcpStream::connect(address).await.is_ok()
+            }
+            #[cfg(target_os = "linux")]
+            Self::Relay(socket_path) => loopback_relay::is_ready(socket_path).await,
+        }
+    }
+
+    // A relayed server gets a port on the agent's loopback that forwards into the sandbox.
+    pub async fn expose(&self) -> io::Result<ExposedServer> {
+        match self {
+            Self::Loopback(port) => Ok(ExposedServer {
+                base_url: format!("http://127.0.0.1:{}", port),
+                relay: None,
+            }),
+            #[cfg(target_os = "linux")]
+            Self::Relay(socket_path) => {
+                let listener: tokio::net::TcpListener =
+                    tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+                let port: u16 = listener.local_addr()?.port();
+                Ok(ExposedServer {
+                    base_url: format!("http://127.0.0.1:{}", port),
+                    relay: Some(tokio::spawn(loopback_relay::forward(
+                        listener,
+                        socket_path.clone(),
+                    ))),
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ExposedServer {
+    pub base_url: String,
+    relay: Option<JoinHandle<()>>,
+}
+
+impl Drop for ExposedServer {
+    fn drop(&mut self) {
+        if let Some(relay) = &self.relay {
+            relay.abort();
+        }
+    }
+}
+
+// Lets the OS pick an unused port. There is a small window before the server binds it, which
+// is fine for a single test run.
+pub fn pick_free_port() -> io::Result<u16> {
+    let listener: TcpListener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    Ok(listener.local_addr()?.port())
+}
+
+// Polls until something accepts connections at the address, the server exits or time runs out.
+pub async fn wait_until_ready(
+    server: &mut SupervisedProcess,
+    address: &ServerAddress,
+    timeout: Duration,
+) -> Readiness {
+    let deadline: Instant = Instant::now() + timeout;
+
+    loop {
+        if let Ok(Some(report)) = server.try_finish() {
+            return Readiness::Exited(report.status);
+        }
+        if address.accepts_connections().await {
+            return Readiness::Ready;
+        }
+        if Instant::now() >= deadline {
+            return Readiness::TimedOut;
+        }
+        time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::process_supervisor::ProcessSupervisor;
+    use std::{env, fs, path::PathBuf, process::Command};
+
+    fn supervisor(name: &str) -> (ProcessSupervisor, PathBuf) {
+        let log_dir: PathBuf =
+            env::temp_dir().join(format!("server_{}_{}", name, std::process::id()));
+        (ProcessSupervisor::new(&log_dir), log_dir)
+    }
+
+    #[tokio::test]
+    async fn tests_readiness_is_detected_for_listening_server() {
+        let port: u16 = pick_free_port().unwrap();
+        let _listener: TcpListener = TcpListener::bind((Ipv4Addr::LOCALHOST, port)).unwrap();
+        let (mut supervisor, log_dir) = supervisor("listening");
+
+        let mut server: SupervisedProcess = supervisor
+            .spawn(
+                "sleep",
+                Command::new("sleep").arg("5"),
+                Duration::from_secs(10),
+            )
+            .unwrap();
+        let readiness: Readiness = wait_until_ready(
+            &mut server,
+            &ServerAddress::Loopback(port),
+            Duration::from_secs(2),
+        )
+        .await;
+        drop(server);
+        let _ = fs::remove_dir_all(&log_dir);
+
+        assert_eq!(readiness, Readiness::Ready);
+    }
+
+    #[tokio::test]
+    async fn tests_exited_or_silent_servers_are_reported() {
+        let port: u16 = pick_free_port().unwrap();
+        let (mut supervisor, log_dir) = supervisor("silent");
+
+        let mut crashed: SupervisedProcess = supervisor
+            .spawn("false", &mut Command::new("false"), Duration::from_secs(10))
+            .unwrap();
+        let readiness: Readiness = wait_until_ready(
+            &mut crashed,
+            &ServerAddress::Loopback(port),
+            Duration::from_secs(5),
+        )
+        .await;
+        assert!(matches!(readiness, Readiness::Exited(status) if !status.success()));
+
+        let mut silent: SupervisedProcess = supervisor
+            .spawn(
+                "sleep",
+                Command::new("sleep").arg("5"),
+                Duration::from_secs(10),
+            )
+            .unwrap();
+        let readiness: Readiness = wait_until_ready(
+            &mut silent,
+            &ServerAddress::Loopback(port),
+            Duration::from_millis(500),
+        )
+        .await;
+        drop(silent);
+        let _ = fs::remove_dir_all(&log_dir);
+        assert_eq!(readiness, Readiness::TimedOut);
+    }
+}
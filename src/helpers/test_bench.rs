@@ -0,0 +1,590 @@
+#[cfg(target_os = "linux")]
+use super::sandbox::Sandbox;
+use super::{
+    command_line::PrintCommand,
+    contract_tester::{run_contract_tests, ContractReport},
+    general::{
+        read_backend_files, save_build_diagnostics, save_contract_report, save_style_report,
+    },
+    process_supervisor::{ProcessReport, ProcessSupervisor, ProcessTimeouts, SupervisedProcess},
+    server::{pick_free_port, wait_until_ready, ExposedServer, Readiness, ServerAddress},
+    workspace::Workspace,
+};
+use crate::models::{
+    agents::agent_context::{AgentContext, AgentEvent, EventSink},
+    general::{
+        api_schema::RouteObject,
+        bug_report::{BugKind, BugReport},
+        diagnostics::{
+            errors_for_fix, parse_cargo_messages, rank_diagnostics, BuildDiagnostics,
+            DiagnosticLevel, StyleReport,
+        },
+        error::AutoGptError,
+        project_files::ProjectFiles,
+    },
+};
+use reqwest::Client;
+use std::{env, process::Command, sync::Arc, time::Duration};
+
+const SERVER_READY_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_DENIED_LINTS: [&str; 2] = ["clippy::correctness", "clippy::suspicious"];
+const DEFAULT_LINT_ROUNDS: u8 = 2;
+const DEFAULT_CPU_SECS: u64 = 1200;
+const DEFAULT_MEMORY_MB: u64 = 8192;
+const DEFAULT_FILE_SIZE_MB: u64 = 2048;
+
+#[derive(Debug)]
+pub struct BuildCheck {
+    pub diagnostics: Option<BuildDiagnostics>,
+    pub bug_report: Option<BugReport>,
+}
+
+#[derive(Debug)]
+pub struct ServerCheck {
+    pub contract_report: ContractReport,
+    pub bug_report: Option<BugReport>,
+}
+
+// The lint levels clippy checks generated code with, from the comma separated lint and group
+// names in BACKEND_CLIPPY_DENY, BACKEND_CLIPPY_WARN and BACKEND_CLIPPY_ALLOW. Denied lints go back
+// to the model for up to BACKEND_LINT_ROUNDS improvement rounds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintPolicy {
+    pub deny: Vec<String>,
+    pub warn: Vec<String>,
+    pub allow: Vec<String>,
+    pub max_rounds: u8,
+}
+
+impl Default for LintPolicy {
+    fn default() -> Self {
+        Self {
+            deny: DEFAULT_DENIED_LINTS
+                .iter()
+                .map(|lint| lint.to_string())
+                .collect(),
+            warn: vec![],
+            allow: vec![],
+            max_rounds: DEFAULT_LINT_ROUNDS,
+        }
+    }
+}
+
+impl LintPolicy {
+    pub fn from_env() -> Self {
+        Self {
+            deny: lints_from_env("BACKEND_CLIPPY_DENY", &DEFAULT_DENIED_LINTS),
+            warn: lints_from_env("BACKEND_CLIPPY_WARN", &[]),
+            allow: lints_from_env("BACKEND_CLIPPY_ALLOW", &[]),
+            max_rounds: env::var("BACKEND_LINT_ROUNDS")
+                .ok()
+                .and_then(|rounds| rounds.parse().ok())
+                .unwrap_or(DEFAULT_LINT_ROUNDS),
+        }
+    }
+
+    // Later flags win in rustc, so allowing a lint inside a denied group works.
+    fn clippy_args(&self) -> Vec<String> {
+        [("-D", &self.deny), ("-W", &self.warn), ("-A", &self.allow)]
+            .into_iter()
+            .flat_map(|(flag, lints)| {
+                lints
+                    .iter()
+                    .flat_map(move |lint| [flag.to_string(), lint.clone()])
+            })
+            .collect()
+    }
+}
+
+fn lints_from_env(name: &str, default: &[&str]) -> Vec<String> {
+    match env::var(name) {
+        Ok(lints) => lints
+            .split(',')
+            .map(str::trim)
+            .filter(|lint| !lint.is_empty())
+            .map(String::from)
+            .collect(),
+        Err(_) => default.iter().map(|lint| lint.to_string()).collect(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SandboxMode {
+    Off,
+    #[default]
+    Namespaces,
+}
+
+impl SandboxMode {
+    pub fn from_env() -> Self {
+        match env::var("BACKEND_SANDBOX").as_deref() {
+            Ok("off") => Self::Off,
+            _ => Self::Namespaces,
+        }
+    }
+}
+
+// Only the Linux sandbox enforces these.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SandboxLimits {
+    pub cpu_secs: u64,
+    pub memory_mb: u64,
+    pub file_size_mb: u64,
+}
+
+impl Default for SandboxLimits {
+    fn default() -> Self {
+        Self {
+            cpu_secs: DEFAULT_CPU_SECS,
+            memory_mb: DEFAULT_MEMORY_MB,
+            file_size_mb: DEFAULT_FILE_SIZE_MB,
+        }
+    }
+}
+
+impl SandboxLimits {
+    pub fn from_env() -> Self {
+        Self {
+            cpu_secs: limit_from_env("BACKEND_SANDBOX_CPU_SECS", DEFAULT_CPU_SECS),
+            memory_mb: limit_from_env("BACKEND_SANDBOX_MEMORY_MB", DEFAULT_MEMORY_MB),
+            file_size_mb: limit_from_env("BACKEND_SANDBOX_FILE_SIZE_MB", DEFAULT_FILE_SIZE_MB),
+        }
+    }
+}
+
+fn limit_from_env(name: &str, default: u64) -> u64 {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+// Builds the code in a workspace and runs its server against the contract tests, with every
+// process supervised and, where namespaces are available, sandboxed.
+#[derive(Debug)]
+pub struct TestBench {
+    position: String,
+    workspace: Workspace,
+    events: Arc<dyn EventSink>,
+    http_client: Client,
+    supervisor: ProcessSupervisor,
+    sandbox: Option<Sandbox>,
+    timeouts: ProcessTimeouts,
+}
+
+impl TestBench {
+    pub fn new(workspace: Workspace, position: &str, context: &AgentContext) -> Self {
+        Self {
+            position: position.to_string(),
+            supervisor: ProcessSupervisor::new(&workspace.logs_dir()),
+            sandbox: configure_sandbox(&workspace, position, context),
+            timeouts: context.config.timeouts,
+            events: context.events.clone(),
+            http_client: context.http_client.clone(),
+            workspace,
+        }
+    }
+
+    fn emit(&self, kind: PrintCommand, message: &str) {
+        self.events.emit(AgentEvent {
+            kind,
+            position: self.position.clone(),
+            message: message.to_string(),
+        });
+    }
+
+    // Sandboxed cargo has no network, so it runs offline once `cargo fetch` has downloaded the
+    // dependencies outside the sandbox.
+    fn cargo_command(
+        &self,
+        subcommand: &str,
+        relay_port: Option<u16>,
+    ) -> Result<Command, AutoGptError> {
+        let mut command: Command = Command::new("cargo");
+        command.current_dir(self.workspace.root());
+        if let Some(sandbox) = &self.sandbox {
+            // Before the subcommand, where `cargo fmt` accepts it too.
+            command.arg("--offline");
+            sandbox
+                .apply(&mut command, relay_port)
+                .map_err(AutoGptError::io("prepare the sandbox"))?;
+        }
+        command.arg(subcommand);
+        Ok(command)
+    }
+
+    pub async fn build(&mut self) -> Result<BuildCheck, AutoGptError> {
+        self.emit(
+            PrintCommand::UnitTest,
+            "Backend Code unit Testing: building project...",
+        );
+
+        if self.sandbox.is_some() {
+            // Fetching only downloads crates and runs none of their build scripts.
+            let fetch_report: ProcessReport = self
+                .supervisor
+                .run(
+                    "cargo-fetch",
+                    Command::new("cargo")
+                        .arg("fetch")
+                        .current_dir(self.workspace.root()),
+                    self.timeouts.build,
+                )
+                .await
+                .map_err(AutoGptError::io("fetch backend dependencies"))?;
+            if !fetch_report.success() {
+                return Ok(BuildCheck {
+                    diagnostics: None,
+                    bug_report: Some(BugReport::new(
+                        BugKind::CompileErrors,
+                        vec![fetch_report.stderr()],
+                    )),
+                });
+            }
+        }
+
+        let mut build_command: Command = self.cargo_command("build", None)?;
+        let build_report: ProcessReport = self
+            .supervisor
+            .run(
+                "cargo-build",
+                build_command.arg("--message-format=json"),
+                self.timeouts.build,
+            )
+            .await
+            .map_err(AutoGptError::io("build the backend application"))?;
+        self.emit(PrintCommand::UnitTest, &build_report.to_string());
+
+        let build_diagnostics: BuildDiagnostics = save_build_diagnostics(
+            &self.workspace,
+            rank_diagnostics(parse_cargo_messages(&build_report.stdout())),
+        )?;
+        let diagnostics_msg: String = format!(
+            "Backend Code unit Testing: build {} reported {} errors and {} warnings",
+            build_diagnostics.build,
+            build_diagnostics.count(DiagnosticLevel::Error),
+            build_diagnostics.count(DiagnosticLevel::Warning)
+        );
+        self.emit(PrintCommand::UnitTest, &diagnostics_msg);
+
+        if build_report.success() {
+            self.emit(
+                PrintCommand::UnitTest,
+                "Backend Code unit Testing: Test server build successful...",
+            );
+            return Ok(BuildCheck {
+                diagnostics: Some(build_diagnostics),
+                bug_report: None,
+            });
+        }
+
+        // Without rustc errors the failure is cargo's own, e.g. a bad manifest.
+        let mut details: Vec<String> = errors_for_fix(
+            &build_diagnostics.diagnostics,
+            &read_backend_files(&self.workspace)?,
+        );
+        if details.is_empty() {
+            details.push(build_report.stderr());
+        }
+        if build_report.timed_out {
+            details.push(format!(
+                "cargo build did not finish within {} seconds",
+                self.timeouts.build.as_secs()
+            ));
+        }
+
+        Ok(BuildCheck {
+            diagnostics: Some(build_diagnostics),
+            bug_report: Some(BugReport::new(BugKind::CompileErrors, details)),
+        })
+    }
+
+    // Formats the code with rustfmt and lints it with clippy. Only meaningful once it builds.
+    pub async fn style_check(
+        &mut self,
+        lint_policy: &LintPolicy,
+    ) -> Result<StyleReport, AutoGptError> {
+        let unformatted: ProjectFiles = read_backend_files(&self.workspace)?;
+        let fmt_report: ProcessReport = self
+            .supervisor
+            .run(
+                "cargo-fmt",
+                &mut self.cargo_command("fmt", None)?,
+                self.timeouts.build,
+            )
+            .await
+            .map_err(AutoGptError::io("run rustfmt"))?;
+        if !fmt_report.success() {
+            let err_msg: String = format!("rustfmt failed: {}", fmt_report.stderr().trim());
+            self.emit(PrintCommand::Issue, &err_msg);
+        }
+
+        let formatted: ProjectFiles = read_backend_files(&self.workspace)?;
+        let reformatted_files: Vec<String> = formatted
+            .iter()
+            .filter(|(path, contents)| unformatted.get(path) != Some(*contents))
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        let mut clippy_command: Command = self.cargo_command("clippy", None)?;
+        let clippy_report: ProcessReport = self
+            .supervisor
+            .run(
+                "cargo-clippy",
+                clippy_command
+                    .arg("--message-format=json")
+                    .arg("--")
+                    .args(lint_policy.clippy_args()),
+                self.timeouts.build,
+            )
+            .await
+            .map_err(AutoGptError::io("run clippy"))?;
+        self.emit(PrintCommand::UnitTest, &clippy_report.to_string());
+
+        let style_report: StyleReport = save_style_report(
+            &self.workspace,
+            reformatted_files,
+            rank_diagnostics(parse_cargo_messages(&clippy_report.stdout())),
+        )?;
+        let style_msg: String = format!(
+            "Backend Code unit Testing: rustfmt reformatted {} files, clippy reported {} denied lints and {} warnings",
+            style_report.reformatted_files.len(),
+            style_report.count(DiagnosticLevel::Error),
+            style_report.count(DiagnosticLevel::Warning)
+        );
+        self.emit(PrintCommand::UnitTest, &style_msg);
+
+        Ok(style_report)
+    }
+
+    // Starts the built server, runs the contract tests against it and stops it again.
+    pub async fn test_server(
+        &mut self,
+        api_endpoints: &[RouteObject],
+    ) -> Result<ServerCheck, AutoGptError> {
+        self.emit(
+            PrintCommand::UnitTest,
+            "Backend Code Unit Testing: String web server...",
+        );
+
+        let port: u16 = pick_free_port().map_err(AutoGptError::io("find a free port"))?;
+
+        let server_address: ServerAddress = match &self.sandbox {
+            #[cfg(target_os = "linux")]
+            Some(sandbox) => ServerAddress::Relay(sandbox.relay_socket().to_path_buf()),
+            _ => ServerAddress::Loopback(port),
+        };
+
+        let mut run_command: Command = self.cargo_command("run", Some(port))?;
+        let mut run_backend_server: SupervisedProcess = self
+            .supervisor
+            .spawn(
+                "cargo-run",
+                run_command.env("PORT", port.to_string()),
+                self.timeouts.run,
+            )
+            .map_err(AutoGptError::io("run the backend application"))?;
+
+        let waiting_msg: String = format!(
+            "Backend Code Unit Testing: Waiting for server (pid {}) on port {}...",
+            run_backend_server.id(),
+            port
+        );
+        self.emit(PrintCommand::UnitTest, waiting_msg.as_str());
+
+        let readiness: Readiness = wait_until_ready(
+            &mut run_backend_server,
+            &server_address,
+            SERVER_READY_TIMEOUT,
+        )
+        .await;
+
+        // The server is stopped whatever happens to the tests, so their errors are kept until then.
+        let contract_report: Result<ContractReport, AutoGptError> = match readiness {
+            Readiness::Ready => {
+                self.run_contract_tests(&server_address, api_endpoints)
+                    .await
+            }
+            _ => Ok(ContractReport::default()),
+        };
+
+        let exited: Option<ProcessReport> = run_backend_server
+            .try_finish()
+            .map_err(AutoGptError::io("check the backend web server"))?;
+        let server_report: ProcessReport = run_backend_server
+            .stop()
+            .map_err(AutoGptError::io("stop the backend web server"))?;
+        let contract_report: ContractReport = contract_report?;
+        self.emit(PrintCommand::UnitTest, &server_report.to_string());
+        let server_stderr: String = server_report.stderr();
+
+        let bug_report: Option<BugReport> = match exited {
+            _ if readiness == Readiness::TimedOut => Some(
+                BugReport::new(
+                    BugKind::ServerNotReady,
+                    vec![format!(
+                        "Nothing accepted connections on port {} within {} seconds",
+                        port,
+                        SERVER_READY_TIMEOUT.as_secs()
+                    )],
+                )
+                .with_server_stderr(&server_stderr),
+            ),
+            Some(report) if report.timed_out => Some(
+                BugReport::new(
+                    BugKind::ServerCrashed,
+                    vec![format!(
+                        "The server was killed after running for {} seconds",
+                        self.timeouts.run.as_secs()
+                    )],
+                )
+                .with_server_stderr(&server_stderr),
+            ),
+            Some(report) => Some(
+                BugReport::new(
+                    BugKind::ServerCrashed,
+                    vec![format!("The server exited with {}", report.status)],
+                )
+                .with_server_stderr(&server_stderr),
+            ),
+            None if !contract_report.passed() => Some(
+                BugReport::new(
+                    BugKind::EndpointFailures,
+                    contract_report
+                        .failures()
+                        .iter()
+                        .map(|failure| {
+                            format!(
+                                "{} {} ({}): {}",
+                                failure.method, failure.route, failure.url, failure.detail
+                            )
+                        })
+                        .collect(),
+                )
+                .with_server_stderr(&server_stderr),
+            ),
+            None => None,
+        };
+
+        Ok(ServerCheck {
+            contract_report,
+            bug_report,
+        })
+    }
+
+    async fn run_contract_tests(
+        &self,
+        server_address: &ServerAddress,
+        api_endpoints: &[RouteObject],
+    ) -> Result<ContractReport, AutoGptError> {
+        self.emit(
+            PrintCommand::UnitTest,
+            "Backend Code Unit Testing: Running contract tests...",
+        );
+
+        let exposed_server: ExposedServer = server_address
+            .expose()
+            .await
+            .map_err(AutoGptError::io("expose the backend web server"))?;
+        let contract_report: ContractReport =
+            run_contract_tests(&self.http_client, &exposed_server.base_url, api_endpoints).await;
+        let contract_table: String = contract_report.render_table();
+        self.emit(PrintCommand::UnitTest, &contract_table);
+        save_contract_report(&self.workspace, &contract_table)?;
+        Ok(contract_report)
+    }
+}
+
+// Falls back to running cargo directly, with a warning, where namespaces are unavailable.
+#[cfg(target_os = "linux")]
+fn configure_sandbox(
+    workspace: &Workspace,
+    position: &str,
+    context: &AgentContext,
+) -> Option<Sandbox> {
+    if context.config.sandbox_mode == SandboxMode::Off {
+        return None;
+    }
+
+    let sandbox: Sandbox = Sandbox::new(
+        workspace.root(),
+        &workspace.relay_socket_path(),
+        context.config.sandbox_limits,
+    );
+    match sandbox.probe() {
+        Ok(()) => Some(sandbox),
+        Err(e) => {
+            warn_unsandboxed(context, position, &e.to_string());
+            None
+        }
+    }
+}
+
+// The sandbox is built on Linux namespaces, so elsewhere generated code always runs directly.
+#[cfg(not(target_os = "linux"))]
+#[derive(Debug)]
+enum Sandbox {}
+
+#[cfg(not(target_os = "linux"))]
+impl Sandbox {
+    fn apply(&self, _command: &mut Command, _relay_port: Option<u16>) -> std::io::Result<()> {
+        match *self {}
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn configure_sandbox(
+    _workspace: &Workspace,
+    position: &str,
+    context: &AgentContext,
+) -> Option<Sandbox> {
+    if context.config.sandbox_mode != SandboxMode::Off {
+        warn_unsandboxed(context, position, "needs Linux namespaces");
+    }
+    None
+}
+
+fn warn_unsandboxed(context: &AgentContext, position: &str, reason: &str) {
+    let warning: String = format!(
+        "Sandbox unavailable ({}), building and running generated code unsandboxed",
+        reason
+    );
+    context.emit(PrintCommand::Issue, position, &warning);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tests_lint_policy_turns_into_clippy_flags() {
+        let lint_policy: LintPolicy = LintPolicy {
+            deny: vec![
+                "clippy::correctness".to_string(),
+                "clippy::unwrap_used".to_string(),
+            ],
+            warn: vec!["clippy::pedantic".to_string()],
+            allow: vec!["dead_code".to_string()],
+            max_rounds: 1,
+        };
+        assert_eq!(
+            lint_policy.clippy_args(),
+            vec![
+                "-D",
+                "clippy::correctness",
+                "-D",
+                "clippy::unwrap_used",
+                "-W",
+                "clippy::pedantic",
+                "-A",
+                "dead_code"
+            ]
+        );
+
+        assert_eq!(
+            lints_from_env("BACKEND_CLIPPY_TEST_UNSET", &DEFAULT_DENIED_LINTS),
+            vec!["clippy::correctness", "clippy::suspicious"]
+        );
+    }
+}
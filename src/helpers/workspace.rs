@@ -0,0 +1,272 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::{project_history::ProjectHistory, scaffold::scaffold_crate};
+
+const DEFAULT_TEMPLATE_DIR: &str = "web_gpt_template";
+const DEFAULT_WORKSPACES_DIR: &str = "workspaces";
+const SKIPPED_DIRS: [&str; 3] = ["target", ".git", ".auto_gpt"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Workspace {
+    root: PathBuf,
+}
+
+impl Workspace {
+    // Copies the web server template in `$TEMPLATE_DIR` (default `./web_gpt_template`) into
+    // `$WORKSPACES_DIR/<run_id>` (default `./workspaces`), relative paths taken from the current
+    // directory.
+    pub fn create(run_id: &str) -> io::Result<Self> {
+        let base_dir: PathBuf = dir_from_env("WORKSPACES_DIR", DEFAULT_WORKSPACES_DIR);
+        let template_dir: PathBuf = dir_from_env("TEMPLATE_DIR", DEFAULT_TEMPLATE_DIR);
+
+        let workspace: Workspace = Self::create_in(&base_dir, &template_dir, run_id)?;
+        ProjectHistory::init(workspace.root())?;
+
+        Ok(workspace)
+    }
+
+    pub fn create_in(base_dir: &Path, template_dir: &Path, run_id: &str) -> io::Result<Self> {
+        fs::create_dir_all(base_dir)?;
+
+        let root: PathBuf = base_dir.join(run_id);
+        fs::create_dir(&root)?;
+        copy_dir_all(template_dir, &root)?;
+
+        let workspace: Workspace = Self { root };
+        scaffold_crate(&workspace)?;
+
+        Ok(workspace)
+    }
+
+    // A fresh copy of this workspace's project, without build output, history or agent files,
+    // next to it as `<run_id>-candidate-<n>`.
+    pub fn create_candidate(&self, candidate: usize) -> io::Result<Self> {
+        let run_id: String = self
+            .root
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let root: PathBuf = self
+            .root
+            .with_file_name(format!("{}-candidate-{}", run_id, candidate));
+        if root.exists() {
+            fs::remove_dir_all(&root)?;
+        }
+        copy_dir_all(&self.root, &root)?;
+
+        Ok(Self { root })
+    }
+
+    pub fn remove(self) -> io::Result<()> {
+        fs::remove_dir_all(&self.root)
+    }
+
+    pub fn open(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn history(&self) -> ProjectHistory {
+        ProjectHistory::new(&self.root)
+    }
+
+    pub fn code_template_path(&self) -> PathBuf {
+        self.root.join("src").join("code_template.rs")
+    }
+
+    pub fn file_path(&self, relative: &str) -> PathBuf {
+        self.root.join(relative)
+    }
+
+    pub fn checkpoint_path(&self) -> PathBuf {
+        self.root.join(".auto_gpt").join("checkpoint.json")
+    }
+
+    pub fn report_path(&self) -> PathBuf {
+        self.root.join(".auto_gpt").join("report.md")
+    }
+
+    pub fn contract_report_path(&self) -> PathBuf {
+        self.root.join(".auto_gpt").join("contract_tests.md")
+    }
+
+    pub fn logs_dir(&self) -> PathBuf {
+        self.root.join(".auto_gpt").join("logs")
+    }
+
+    pub fn diagnostics_log_path(&self) -> PathBuf {
+        self.root.join(".auto_gpt").join("diagnostics.jsonl")
+    }
+
+    pub fn style_log_path(&self) -> PathBuf {
+        self.root.join(".auto_gpt").join("style.jsonl")
+    }
+
+    pub fn candidate_scores_path(&self) -> PathBuf {
+        self.root.join(".auto_gpt").join("candidates.json")
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn relay_socket_path(&self) -> PathBuf {
+        self.root.join(".auto_gpt").join("server.sock")
+    }
+
+    pub fn openapi_json_path(&self) -> PathBuf {
+        self.root.join("schemas").join("openapi.json")
+    }
+
+    pub fn openapi_yaml_path(&self) -> PathBuf {
+        self.root.join("schemas").join("openapi.yaml")
+    }
+}
+
+pub fn new_run_id() -> String {
+    let millis: u128 = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or_default();
+
+    format!("run-{}-{}", millis, process::id())
+}
+
+fn dir_from_env(name: &str, default: &str) -> PathBuf {
+    env::var(name)
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(default))
+}
+
+fn copy_dir_all(src: &Path, dst: &Path) -> io::Result<()> {
+    fs::create_dir_all(dst)?;
+
+    for entry in fs::read_dir(src)? {
+        let entry: fs::DirEntry = entry?;
+        let file_name = entry.file_name();
+
+        if SKIPPED_DIRS.iter().any(|skipped| file_name == *skipped) {
+            continue;
+        }
+
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &dst.join(&file_name))?;
+        } else {
+            fs::copy(entry.path(), dst.join(&file_name))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static TEMP_DIR_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let count: usize = TEMP_DIR_COUNT.fetch_add(1, Ordering::SeqCst);
+        let dir: PathBuf = env::temp_dir().join(format!("{}-{}-{}", name, new_run_id(), count));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn tests_workspace_is_copied_from_template() {
+        let template_dir: PathBuf = temp_dir("template");
+        fs::create_dir_all(template_dir.join("src")).unwrap();
+        fs::create_dir_all(template_dir.join("target").join("debug")).unwrap();
+        fs::write(template_dir.join("src").join("main.rs"), "fn main() {}").unwrap();
+        fs::write(
+            template_dir.join("src").join("code_template.rs"),
+            "// template",
+        )
+        .unwrap();
+
+        let base_dir: PathBuf = temp_dir("workspaces");
+        let workspace: Workspace = Workspace::create_in(&base_dir, &template_dir, "run-a").unwrap();
+
+        assert_eq!(workspace.root(), base_dir.join("run-a"));
+        assert_eq!(
+            fs::read_to_string(workspace.file_path("src/main.rs")).unwrap(),
+            "fn main() {}"
+        );
+        assert!(workspace.code_template_path().exists());
+        assert!(workspace.root().join("Cargo.toml").exists());
+        assert!(workspace.root().join(".gitignore").exists());
+        assert!(workspace.root().join("README.md").exists());
+        assert!(!workspace.root().join("target").exists());
+
+        fs::write(
+            workspace.file_path("src/main.rs"),
+            "fn main() { println!(); }",
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(template_dir.join("src").join("main.rs")).unwrap(),
+            "fn main() {}"
+        );
+
+        let _ = fs::remove_dir_all(&template_dir);
+        let _ = fs::remove_dir_all(&base_dir);
+    }
+
+    #[test]
+    fn tests_workspace_runs_do_not_clobber_each_other() {
+        let template_dir: PathBuf = temp_dir("template");
+        fs::write(template_dir.join("README.md"), "template").unwrap();
+
+        let base_dir: PathBuf = temp_dir("workspaces");
+        let first: Workspace = Workspace::create_in(&base_dir, &template_dir, "run-a").unwrap();
+        let second: Workspace = Workspace::create_in(&base_dir, &template_dir, "run-b").unwrap();
+        assert_ne!(first.root(), second.root());
+
+        let duplicate = Workspace::create_in(&base_dir, &template_dir, "run-a");
+        assert_eq!(duplicate.unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+
+        let _ = fs::remove_dir_all(&template_dir);
+        let _ = fs::remove_dir_all(&base_dir);
+    }
+
+    #[test]
+    fn tests_candidate_workspaces_copy_only_the_project() {
+        let template_dir: PathBuf = temp_dir("template");
+        fs::create_dir_all(template_dir.join("src")).unwrap();
+        fs::write(template_dir.join("src").join("main.rs"), "fn main() {}").unwrap();
+
+        let base_dir: PathBuf = temp_dir("workspaces");
+        let workspace: Workspace = Workspace::create_in(&base_dir, &template_dir, "run-a").unwrap();
+        fs::create_dir_all(workspace.logs_dir()).unwrap();
+        fs::create_dir_all(workspace.root().join("target")).unwrap();
+
+        let candidate: Workspace = workspace.create_candidate(2).unwrap();
+        assert_eq!(candidate.root(), base_dir.join("run-a-candidate-2"));
+        assert!(candidate.file_path("src/main.rs").exists());
+        assert!(candidate.root().join("Cargo.toml").exists());
+        assert!(!candidate.root().join(".auto_gpt").exists());
+        assert!(!candidate.root().join("target").exists());
+
+        fs::write(candidate.file_path("src/main.rs"), "fn main() { todo!() }").unwrap();
+        let recreated: Workspace = workspace.create_candidate(2).unwrap();
+        assert_eq!(
+            fs::read_to_string(recreated.file_path("src/main.rs")).unwrap(),
+            "fn main() {}"
+        );
+
+        recreated.remove().unwrap();
+        assert!(!base_dir.join("run-a-candidate-2").exists());
+        assert!(workspace.file_path("src/main.rs").exists());
+
+        let _ = fs::remove_dir_all(&template_dir);
+        let _ = fs::remove_dir_all(&base_dir);
+    }
+}
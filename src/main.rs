@@ -10,18 +10,74 @@ mod apis;
 mod helpers;
 mod models;
 
-use helpers::command_line::get_user_response;
+use helpers::{
+    command_line::{get_cli_option, get_user_response},
+    process_supervisor::kill_on_ctrl_c,
+    workspace::{new_run_id, Workspace},
+};
 
-use crate::models::agent_manager::managing_agent;
-use models::agent_manager::managing_agent::ManagingAgent;
+use models::{
+    agent_manager::{
+        agent_registry::AgentRegistry,
+        managing_agent::ManagingAgent,
+        pipeline::{pipeline_from_cli_and_env, AgentSpec},
+        project_summary::ProjectSummary,
+    },
+    agents::agent_context::{CancellationToken, ConsoleEvents},
+    general::{api_schema::RouteObject, error::AutoGptError, openapi::load_openapi_routes},
+};
+use std::{path::Path, process::ExitCode, sync::Arc};
 
+// Exits with 0 when every agent finished, otherwise with the code of the first error: 1 an agent
+// gave up, 2 bad configuration, 3 the model failed, 4 a file or process failed, 5 the user stopped,
+// 130 cancelled with Ctrl-C.
 #[tokio::main]
-async fn main() {
-    let user_req: String = get_user_response("What are website are we building today?");
+async fn main() -> ExitCode {
+    let cancellation: CancellationToken = CancellationToken::default();
+    kill_on_ctrl_c(cancellation.clone(), Arc::new(ConsoleEvents));
 
-    let mut managing_agent: ManagingAgent = ManagingAgent::new(user_req).await.expect("Error creating agent");
+    match run(cancellation).await {
+        Ok(summary) => {
+            println!("{}", summary);
+            ExitCode::from(summary.exit_code())
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+async fn run(cancellation: CancellationToken) -> Result<ProjectSummary, AutoGptError> {
+    let registry: AgentRegistry = AgentRegistry::built_in();
 
-    managing_agent.execute_project().await;
+    let mut managing_agent: ManagingAgent = match get_cli_option("--resume") {
+        Some(checkpoint_path) => {
+            ManagingAgent::resume(Path::new(&checkpoint_path), &registry, cancellation)?
+        }
+        None => {
+            let pipeline: Vec<AgentSpec> = pipeline_from_cli_and_env()?;
+            registry.check_pipeline(&pipeline)?;
+            let api_contract: Option<Vec<RouteObject>> = get_cli_option("--openapi")
+                .map(|path| load_openapi_routes(Path::new(&path)))
+                .transpose()
+                .map_err(|e| AutoGptError::Config(e.to_string()))?;
+            let user_req: String = get_user_response("What are website are we building today?")
+                .map_err(AutoGptError::io("read the project request"))?;
+            let workspace: Workspace = Workspace::create(&new_run_id())
+                .map_err(AutoGptError::io("create the workspace"))?;
+            ManagingAgent::new(
+                user_req,
+                api_contract,
+                &registry,
+                pipeline,
+                workspace,
+                cancellation,
+            )
+            .await?
+        }
+    };
 
-    dbg!(managing_agent);
+    let summary: ProjectSummary = managing_agent.execute_project().await?;
+    Ok(summary)
 }
@@ -1,13 +1,17 @@
 use crate::models::{agent_basic::basic_traits::BasicTraits, general::llm::Message};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
 pub enum AgentState {
+    #[default]
     Discovery,
     Working,
     UnitTesting,
     Finished,
+    Failed,
 }
 
+#[allow(dead_code)]
 #[derive(Debug)]
 pub struct BasicAgent {
     pub objective: String,
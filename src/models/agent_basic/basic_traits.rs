@@ -1,5 +1,6 @@
 use crate::models::{agent_basic::basic_agent::AgentState, general::llm::Message};
 
+#[allow(dead_code)]
 pub trait BasicTraits {
     fn new(objective: String, position: String) -> Self;
     fn update_state(&mut self, state: AgentState);
@@ -7,4 +8,4 @@ pub trait BasicTraits {
     fn get_position(&self) -> &String;
     fn get_state(&self) -> &AgentState;
     fn get_memory(&self) -> &Vec<Message>;
-}
\ No newline at end of file
+}
@@ -1,3 +1,2 @@
-
 pub mod basic_agent;
-pub mod basic_traits;
\ No newline at end of file
+pub mod basic_traits;
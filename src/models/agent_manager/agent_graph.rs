@@ -0,0 +1,660 @@
+use crate::{
+    helpers::command_line::PrintCommand,
+    models::{
+        agent_basic::basic_agent::AgentState,
+        agent_manager::project_summary::AgentOutcome,
+        agents::{
+            agent_context::AgentContext,
+            agent_traits::{FactSheet, FactSheetField, FactSheetInput, SpecialFunctions},
+        },
+        general::error::AutoGptError,
+    },
+};
+use std::{mem, sync::Arc};
+use tokio::sync::{
+    mpsc::{self, UnboundedReceiver, UnboundedSender},
+    Mutex,
+};
+
+type SharedAgent = Arc<Mutex<Box<dyn SpecialFunctions>>>;
+// The agent's factsheet and result, or why its task ended without one.
+type AgentRun = (usize, Result<(FactSheet, Result<(), AutoGptError>), String>);
+
+// Sends an agent's run back to the graph, or a failure if its task is dropped before that.
+struct RunReporter {
+    agent: usize,
+    sender: Option<UnboundedSender<AgentRun>>,
+}
+
+impl RunReporter {
+    fn report(mut self, run: Result<(FactSheet, Result<(), AutoGptError>), String>) {
+        if let Some(sender) = self.sender.take() {
+            let _ = sender.send((self.agent, run));
+        }
+    }
+}
+
+impl Drop for RunReporter {
+    fn drop(&mut self) {
+        if let Some(sender) = self.sender.take() {
+            let reason: String = "the agent's task stopped before reporting back".to_string();
+            let _ = sender.send((self.agent, Err(reason)));
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentDeclaration {
+    pub position: String,
+    pub reads: Vec<FactSheetInput>,
+    pub writes: Vec<FactSheetField>,
+}
+
+// Which agents have to finish before each one can start: every agent writing a field another
+// reads runs first, and agents writing the same field otherwise run in pipeline order so the
+// later one's output is kept. Agents with no path between them run at the same time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentGraph {
+    agents: Vec<AgentDeclaration>,
+    dependencies: Vec<Vec<usize>>,
+}
+
+impl AgentGraph {
+    pub fn new(agents: &[Box<dyn SpecialFunctions>]) -> Result<Self, AutoGptError> {
+        Self::from_declarations(
+            agents
+                .iter()
+                .map(|agent| AgentDeclaration {
+                    position: agent.get_attributes_from_agent().position.clone(),
+                    reads: agent.reads(),
+                    writes: agent.writes(),
+                })
+                .collect(),
+        )
+    }
+
+    pub fn from_declarations(agents: Vec<AgentDeclaration>) -> Result<Self, AutoGptError> {
+        let mut dependencies: Vec<Vec<usize>> = agents
+            .iter()
+            .enumerate()
+            .map(|(reader, declaration)| {
+                agents
+                    .iter()
+                    .enumerate()
+                    .filter(|(writer, writer_declaration)| {
+                        *writer != reader
+                            && declaration
+                                .reads
+                                .iter()
+                                .any(|input| writer_declaration.writes.contains(&input.field()))
+                    })
+                    .map(|(writer, _)| writer)
+                    .collect()
+            })
+            .collect();
+
+        for later in 0..agents.len() {
+            for earlier in 0..later {
+                let overlapping: bool = agents[later]
+                    .writes
+                    .iter()
+                    .any(|field| agents[earlier].writes.contains(field));
+                if overlapping
+                    && !waits_for(&dependencies, later, earlier)
+                    && !waits_for(&dependencies, earlier, later)
+                {
+                    dependencies[later].push(earlier);
+                }
+            }
+        }
+
+        let graph: AgentGraph = Self {
+            agents,
+            dependencies,
+        };
+
+        let ordered: Vec<usize> = graph.order();
+        if ordered.len() < graph.agents.len() {
+            let cycle: Vec<&str> = (0..graph.agents.len())
+                .filter(|agent| !ordered.contains(agent))
+                .map(|agent| graph.agents[agent].position.as_str())
+                .collect();
+            return Err(AutoGptError::Config(format!(
+                "agents depend on each other's output: {}",
+                cycle.join(", ")
+            )));
+        }
+
+        Ok(graph)
+    }
+
+    // A topological order; agents stuck in a cycle are left out.
+    fn order(&self) -> Vec<usize> {
+        let mut ordered: Vec<usize> = vec![];
+        while let Some(next) = (0..self.agents.len()).find(|agent| {
+            !ordered.contains(agent)
+                && self.dependencies[*agent]
+                    .iter()
+                    .all(|dependency| ordered.contains(dependency))
+        }) {
+            ordered.push(next);
+        }
+        ordered
+    }
+
+    fn missing_inputs(&self, agent: usize, factsheet: &FactSheet) -> Vec<&'static str> {
+        self.agents[agent]
+            .reads
+            .iter()
+            .filter_map(|input| match input {
+                FactSheetInput::Required(field) if !field.is_set(factsheet) => Some(field.name()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // Starts every agent whose dependencies finished, each on its own copy of the factsheet and
+    // with its own context, and merges back the fields it writes. Nothing new starts after a
+    // failure. Only an agent whose task is still holding it afterwards is an error here.
+    pub async fn run(
+        &self,
+        agents: &mut Vec<Box<dyn SpecialFunctions>>,
+        contexts: &[AgentContext],
+        factsheet: &mut FactSheet,
+    ) -> Result<Vec<(String, AgentOutcome)>, AutoGptError> {
+        let mut outcomes: Vec<Option<AgentOutcome>> = agents
+            .iter()
+            .map(|agent| match agent.get_attributes_from_agent().state {
+                AgentState::Finished => Some(AgentOutcome::Finished),
+                _ => None,
+            })
+            .collect();
+        let mut started: Vec<bool> = outcomes.iter().map(Option::is_some).collect();
+        let shared: Vec<SharedAgent> = mem::take(agents)
+            .into_iter()
+            .map(|agent| Arc::new(Mutex::new(agent)))
+            .collect();
+        let (sender, mut receiver): (UnboundedSender<AgentRun>, UnboundedReceiver<AgentRun>) =
+            mpsc::unbounded_channel();
+        let mut running: usize = 0;
+
+        loop {
+            let failed: bool = outcomes
+                .iter()
+                .any(|outcome| matches!(outcome, Some(AgentOutcome::Failed(_))));
+            let ready: Vec<usize> = (0..shared.len())
+                .filter(|agent| {
+                    !failed
+                        && !started[*agent]
+                        && self.dependencies[*agent].iter().all(|dependency| {
+                            matches!(outcomes[*dependency], Some(AgentOutcome::Finished))
+                        })
+                })
+                .collect();
+
+            for agent in ready {
+                started[agent] = true;
+                let position: String = self.agents[agent].position.clone();
+
+                let missing: Vec<&'static str> = self.missing_inputs(agent, factsheet);
+                if !missing.is_empty() {
+                    let e: AutoGptError = AutoGptError::MissingInputs {
+                        position,
+                        fields: missing,
+                    };
+                    contexts[agent].emit(
+                        PrintCommand::Issue,
+                        &self.agents[agent].position,
+                        &e.to_string(),
+                    );
+                    outcomes[agent] = Some(AgentOutcome::Failed(e));
+                    break;
+                }
+
+                let shared_agent: SharedAgent = shared[agent].clone();
+                let agent_context: AgentContext = contexts[agent].clone();
+                let mut agent_factsheet: FactSheet = factsheet.clone();
+                let reporter: RunReporter = RunReporter {
+                    agent,
+                    sender: Some(sender.clone()),
+                };
+                running += 1;
+                // The agent runs in a task of its own so a panic is reported against it.
+                tokio::spawn(async move {
+                    let execution = tokio::spawn(async move {
+                        let mut agent = shared_agent.lock().await;
+                        let agent_res: Result<(), AutoGptError> =
+                            agent.execute(&agent_context, &mut agent_factsheet).await;
+                        (agent_factsheet, agent_res)
+                    });
+                    reporter.report(
+                        execution
+                            .await
+                            .map_err(|e| format!("the agent panicked: {}", e)),
+                    );
+                });
+            }
+
+            if running == 0 {
+                break;
+            }
+            // The graph keeps a sender of its own, so this only waits for the next report.
+            let Some((agent, execution)) = receiver.recv().await else {
+                break;
+            };
+            running -= 1;
+
+            let position: String = self.agents[agent].position.clone();
+            let agent_res: Result<(), AutoGptError> = match execution {
+                Ok((agent_factsheet, agent_res)) => {
+                    for field in &self.agents[agent].writes {
+                        field.copy(&agent_factsheet, factsheet);
+                    }
+                    agent_res
+                }
+                Err(reason) => Err(AutoGptError::AgentFailed {
+                    position: position.clone(),
+                    reason,
+                }),
+            };
+
+            outcomes[agent] = Some(match agent_res {
+                Ok(()) => AgentOutcome::Finished,
+                Err(e) => {
+                    let err_msg: String = format!("Stopping the project: {}", e);
+                    contexts[agent].emit(PrintCommand::Issue, &position, &err_msg);
+                    AgentOutcome::Failed(e)
+                }
+            });
+        }
+
+        *agents = shared
+            .into_iter()
+            .zip(&self.agents)
+            .map(|(agent, declaration)| {
+                Arc::try_unwrap(agent).map(Mutex::into_inner).map_err(|_| {
+                    AutoGptError::AgentFailed {
+                        position: declaration.position.clone(),
+                        reason: "its task outlived the pipeline".to_string(),
+                    }
+                })
+            })
+            .collect::<Result<Vec<Box<dyn SpecialFunctions>>, AutoGptError>>()?;
+
+        Ok(self
+            .agents
+            .iter()
+            .zip(outcomes)
+            .map(|(declaration, outcome)| {
+                (
+                    declaration.position.clone(),
+                    outcome.unwrap_or(AgentOutcome::NotRun),
+                )
+            })
+            .collect())
+    }
+}
+
+// Whether `agent` has to wait for `other`, directly or through the agents in between.
+fn waits_for(dependencies: &[Vec<usize>], agent: usize, other: usize) -> bool {
+    let mut visited: Vec<usize> = vec![];
+    let mut pending: Vec<usize> = vec![agent];
+    while let Some(next) = pending.pop() {
+        for dependency in &dependencies[next] {
+            if *dependency == other {
+                return true;
+            }
+            if !visited.contains(dependency) {
+                visited.push(*dependency);
+                pending.push(*dependency);
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        apis::llm_provider::LlmProvider,
+        helpers::workspace::{new_run_id, Workspace},
+        models::{
+            agent_basic::basic_agent::BasicAgent,
+            agent_manager::checkpoint::{AgentProgress, Checkpointer},
+            agents::{
+                agent_context::{AgentConfig, CancellationToken, ConsoleEvents, TerminalUser},
+                agent_traits::ProjectScope,
+            },
+            general::llm::Message,
+        },
+    };
+    use async_trait::async_trait;
+    use reqwest::Client;
+    use std::{env, time::Duration};
+    use tokio::{sync::Barrier, time};
+
+    #[derive(Debug)]
+    struct NoProvider;
+
+    #[async_trait]
+    impl LlmProvider for NoProvider {
+        async fn chat(
+            &self,
+            _messages: Vec<Message>,
+            _model: &str,
+        ) -> Result<String, AutoGptError> {
+            Err(AutoGptError::Llm("no model in tests".to_string()))
+        }
+    }
+
+    #[derive(Debug)]
+    struct FakeAgent {
+        attributes: BasicAgent,
+        reads: Vec<FactSheetInput>,
+        writes: Vec<FactSheetField>,
+        barrier: Option<Arc<Barrier>>,
+    }
+
+    impl FakeAgent {
+        fn new(position: &str, reads: Vec<FactSheetInput>, writes: Vec<FactSheetField>) -> Self {
+            Self {
+                attributes: BasicAgent {
+                    objective: "Test the pipeline".to_string(),
+                    position: position.to_string(),
+                    state: AgentState::Discovery,
+                    memory: vec![],
+                },
+                reads,
+                writes,
+                barrier: None,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl SpecialFunctions for FakeAgent {
+        fn get_attributes_from_agent(&self) -> &BasicAgent {
+            &self.attributes
+        }
+
+        fn get_attributes_mut(&mut self) -> &mut BasicAgent {
+            &mut self.attributes
+        }
+
+        fn get_checkpointer(&self) -> Option<&Checkpointer> {
+            None
+        }
+
+        fn attach_checkpointer(&mut self, _checkpointer: Checkpointer) {}
+
+        fn progress(&self) -> AgentProgress {
+            AgentProgress::new(&self.attributes.position, self.attributes.state)
+        }
+
+        fn restore_progress(&mut self, progress: &AgentProgress) {
+            self.attributes.state = progress.state;
+        }
+
+        fn reads(&self) -> Vec<FactSheetInput> {
+            self.reads.clone()
+        }
+
+        fn writes(&self) -> Vec<FactSheetField> {
+            self.writes.clone()
+        }
+
+        async fn execute(
+            &mut self,
+            _context: &AgentContext,
+            factsheet: &mut FactSheet,
+        ) -> Result<(), AutoGptError> {
+            // Both agents sharing the barrier have to be running for either to get past it.
+            if let Some(barrier) = &self.barrier {
+                barrier.wait().await;
+            }
+            for field in &self.writes {
+                match field {
+                    FactSheetField::ProjectScope => {
+                        factsheet.project_scope = Some(ProjectScope {
+                            is_crud_required: true,
+                            is_user_login_and_logout: false,
+                            is_external_urls_required: false,
+                        })
+                    }
+                    FactSheetField::ExternalUrls => factsheet.external_urls = Some(vec![]),
+                    FactSheetField::ApiEndpointSchema => {
+                        factsheet.api_endpoint_schema = Some(vec![])
+                    }
+                    _ => {}
+                }
+            }
+            self.attributes.state = AgentState::Finished;
+            Ok(())
+        }
+    }
+
+    fn test_context() -> AgentContext {
+        AgentContext {
+            provider: Arc::new(NoProvider),
+            http_client: Client::new(),
+            config: AgentConfig::default(),
+            // Nothing in these tests writes to the workspace, so it is never created.
+            workspace: Workspace::open(&env::temp_dir().join(format!("graph-{}", new_run_id()))),
+            events: Arc::new(ConsoleEvents),
+            user: Arc::new(TerminalUser),
+            cancellation: CancellationToken::default(),
+        }
+    }
+
+    fn declaration(
+        position: &str,
+        reads: Vec<FactSheetInput>,
+        writes: Vec<FactSheetField>,
+    ) -> AgentDeclaration {
+        AgentDeclaration {
+            position: position.to_string(),
+            reads,
+            writes,
+        }
+    }
+
+    #[test]
+    fn tests_graph_orders_readers_after_writers() {
+        let graph: AgentGraph = AgentGraph::from_declarations(vec![
+            declaration(
+                "Backend Developer",
+                vec![
+                    FactSheetInput::Required(FactSheetField::ProjectDescription),
+                    FactSheetInput::Optional(FactSheetField::ProjectScope),
+                    FactSheetInput::Optional(FactSheetField::ApiEndpointSchema),
+                ],
+                vec![
+                    FactSheetField::BackendCode,
+                    FactSheetField::ApiEndpointSchema,
+                ],
+            ),
+            declaration(
+                "Solutions Architect",
+                vec![FactSheetInput::Required(FactSheetField::ProjectDescription)],
+                vec![FactSheetField::ProjectScope],
+            ),
+            declaration(
+                "Url Scout",
+                vec![FactSheetInput::Required(FactSheetField::ProjectDescription)],
+                vec![FactSheetField::ExternalUrls],
+            ),
+        ])
+        .unwrap();
+
+        assert_eq!(graph.dependencies[0], vec![1]);
+        assert!(graph.dependencies[1].is_empty());
+        assert!(graph.dependencies[2].is_empty());
+        assert_eq!(graph.order(), vec![1, 0, 2]);
+
+        let cycle: AutoGptError = AgentGraph::from_declarations(vec![
+            declaration(
+                "Writer",
+                vec![FactSheetInput::Optional(FactSheetField::BackendCode)],
+                vec![FactSheetField::ProjectScope],
+            ),
+            declaration(
+                "Reviewer",
+                vec![FactSheetInput::Required(FactSheetField::ProjectScope)],
+                vec![FactSheetField::BackendCode],
+            ),
+        ])
+        .unwrap_err();
+        assert_eq!(
+            cycle.to_string(),
+            "configuration error: agents depend on each other's output: Writer, Reviewer"
+        );
+    }
+
+    #[test]
+    fn tests_writers_of_the_same_field_run_in_pipeline_order() {
+        let graph: AgentGraph = AgentGraph::from_declarations(vec![
+            declaration(
+                "Backend Developer",
+                vec![FactSheetInput::Required(FactSheetField::ProjectDescription)],
+                vec![FactSheetField::BackendCode],
+            ),
+            declaration(
+                "Backend Reviewer",
+                vec![FactSheetInput::Required(FactSheetField::ProjectDescription)],
+                vec![FactSheetField::BackendCode],
+            ),
+        ])
+        .unwrap();
+        assert!(graph.dependencies[0].is_empty());
+        assert_eq!(graph.dependencies[1], vec![0]);
+
+        // A read already orders them the other way round, which is not a cycle.
+        let graph: AgentGraph = AgentGraph::from_declarations(vec![
+            declaration(
+                "Backend Reviewer",
+                vec![FactSheetInput::Required(FactSheetField::ProjectScope)],
+                vec![FactSheetField::BackendCode],
+            ),
+            declaration(
+                "Backend Developer",
+                vec![FactSheetInput::Required(FactSheetField::ProjectDescription)],
+                vec![FactSheetField::BackendCode, FactSheetField::ProjectScope],
+            ),
+        ])
+        .unwrap();
+        assert_eq!(graph.dependencies[0], vec![1]);
+        assert!(graph.dependencies[1].is_empty());
+        assert_eq!(graph.order(), vec![1, 0]);
+    }
+
+    #[tokio::test]
+    async fn tests_independent_agents_run_concurrently() {
+        let barrier: Arc<Barrier> = Arc::new(Barrier::new(2));
+        let mut scoping: FakeAgent = FakeAgent::new(
+            "Solutions Architect",
+            vec![FactSheetInput::Required(FactSheetField::ProjectDescription)],
+            vec![FactSheetField::ProjectScope],
+        );
+        scoping.barrier = Some(barrier.clone());
+        let mut scouting: FakeAgent = FakeAgent::new(
+            "Url Scout",
+            vec![FactSheetInput::Required(FactSheetField::ProjectDescription)],
+            vec![FactSheetField::ExternalUrls],
+        );
+        scouting.barrier = Some(barrier);
+        let backend: FakeAgent = FakeAgent::new(
+            "Backend Developer",
+            vec![
+                FactSheetInput::Required(FactSheetField::ProjectScope),
+                FactSheetInput::Required(FactSheetField::ExternalUrls),
+            ],
+            vec![FactSheetField::ApiEndpointSchema],
+        );
+
+        let mut agents: Vec<Box<dyn SpecialFunctions>> =
+            vec![Box::new(backend), Box::new(scoping), Box::new(scouting)];
+        let graph: AgentGraph = AgentGraph::new(&agents).unwrap();
+        let mut factsheet: FactSheet =
+            FactSheet::described("build a website that tracks fitness progress");
+
+        let outcomes: Vec<(String, AgentOutcome)> = time::timeout(
+            Duration::from_secs(5),
+            graph.run(&mut agents, &vec![test_context(); 3], &mut factsheet),
+        )
+        .await
+        .expect("independent agents were run one after the other")
+        .unwrap();
+
+        assert!(outcomes
+            .iter()
+            .all(|(_, outcome)| matches!(outcome, AgentOutcome::Finished)));
+        assert!(factsheet.project_scope.is_some());
+        assert_eq!(factsheet.external_urls, Some(vec![]));
+        assert_eq!(factsheet.api_endpoint_schema, Some(vec![]));
+        assert_eq!(agents.len(), 3);
+    }
+
+    #[test]
+    fn tests_dropped_agent_task_is_reported_as_failed() {
+        let (sender, mut receiver): (UnboundedSender<AgentRun>, UnboundedReceiver<AgentRun>) =
+            mpsc::unbounded_channel();
+        drop(RunReporter {
+            agent: 1,
+            sender: Some(sender),
+        });
+
+        match receiver.try_recv() {
+            Ok((1, Err(reason))) => {
+                assert_eq!(reason, "the agent's task stopped before reporting back")
+            }
+            run => panic!("the dropped task was not reported: {:?}", run.is_ok()),
+        }
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn tests_agent_with_missing_inputs_is_not_started() {
+        let reviewer: FakeAgent = FakeAgent::new(
+            "Code Reviewer",
+            vec![FactSheetInput::Required(FactSheetField::BackendCode)],
+            vec![FactSheetField::ProjectScope],
+        );
+        let backend: FakeAgent = FakeAgent::new(
+            "Backend Developer",
+            vec![FactSheetInput::Required(FactSheetField::ProjectScope)],
+            vec![FactSheetField::ApiEndpointSchema],
+        );
+
+        let mut agents: Vec<Box<dyn SpecialFunctions>> =
+            vec![Box::new(reviewer), Box::new(backend)];
+        let graph: AgentGraph = AgentGraph::new(&agents).unwrap();
+        let mut factsheet: FactSheet =
+            FactSheet::described("build a website that tracks fitness progress");
+
+        let outcomes: Vec<(String, AgentOutcome)> = graph
+            .run(&mut agents, &vec![test_context(); 2], &mut factsheet)
+            .await
+            .unwrap();
+
+        match &outcomes[0].1 {
+            AgentOutcome::Failed(e) => {
+                assert_eq!(
+                    e.to_string(),
+                    "Code Reviewer cannot start without backend_code"
+                );
+                assert_eq!(e.exit_code(), 2);
+            }
+            outcome => panic!("reviewer should not have started: {:?}", outcome),
+        }
+        assert!(matches!(outcomes[1].1, AgentOutcome::NotRun));
+        assert_eq!(
+            agents[0].get_attributes_from_agent().state,
+            AgentState::Discovery
+        );
+        assert_eq!(
+            factsheet,
+            FactSheet::described("build a website that tracks fitness progress")
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use crate::models::{
+    agent_manager::pipeline::AgentSpec,
+    agents::{
+        agent_architect::AgentSolutionArchitect, agent_backend::AgentBackendDeveloper,
+        agent_traits::SpecialFunctions,
+    },
+    general::error::AutoGptError,
+};
+
+pub type AgentFactory = fn() -> Box<dyn SpecialFunctions>;
+
+// The agents a pipeline can name. Projects with extra agents register their factories here.
+#[derive(Debug, Clone)]
+pub struct AgentRegistry {
+    factories: Vec<(String, AgentFactory)>,
+}
+
+impl AgentRegistry {
+    pub fn built_in() -> Self {
+        let mut registry: AgentRegistry = Self { factories: vec![] };
+        registry.register("architect", || Box::new(AgentSolutionArchitect::new()));
+        registry.register("backend", || Box::new(AgentBackendDeveloper::new()));
+        registry
+    }
+
+    // A factory registered under an existing name replaces it.
+    pub fn register(&mut self, name: &str, factory: AgentFactory) {
+        self.factories.retain(|(registered, _)| registered != name);
+        self.factories.push((name.to_string(), factory));
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.factories
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    pub fn create_pipeline(
+        &self,
+        pipeline: &[AgentSpec],
+    ) -> Result<Vec<Box<dyn SpecialFunctions>>, AutoGptError> {
+        pipeline.iter().map(|spec| self.create(spec)).collect()
+    }
+
+    // Checks every name before anything is asked of the user or the model.
+    pub fn check_pipeline(&self, pipeline: &[AgentSpec]) -> Result<(), AutoGptError> {
+        pipeline
+            .iter()
+            .try_for_each(|spec| self.factory(&spec.agent).map(|_| ()))
+    }
+
+    pub fn create(&self, spec: &AgentSpec) -> Result<Box<dyn SpecialFunctions>, AutoGptError> {
+        let factory: AgentFactory = self.factory(&spec.agent)?;
+        let mut agent: Box<dyn SpecialFunctions> = factory();
+        if let Some(objective) = &spec.objective {
+            agent.get_attributes_mut().objective = objective.clone();
+        }
+        Ok(agent)
+    }
+
+    fn factory(&self, agent: &str) -> Result<AgentFactory, AutoGptError> {
+        self.factories
+            .iter()
+            .find(|(name, _)| name == agent)
+            .map(|(_, factory)| *factory)
+            .ok_or_else(|| {
+                AutoGptError::Config(format!(
+                    "unknown agent '{}', expected one of {}",
+                    agent,
+                    self.names().join(", ")
+                ))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tests_registry_creates_agents_by_name() {
+        let mut registry: AgentRegistry = AgentRegistry::built_in();
+        let spec: AgentSpec = AgentSpec {
+            objective: Some("Only decide the project scope".to_string()),
+            ..AgentSpec::named("architect")
+        };
+
+        let architect: Box<dyn SpecialFunctions> = registry.create(&spec).unwrap();
+        assert_eq!(
+            architect.get_attributes_from_agent().position,
+            "Solutions Architect"
+        );
+        assert_eq!(
+            architect.get_attributes_from_agent().objective,
+            "Only decide the project scope"
+        );
+
+        let unknown: AutoGptError = registry.create(&AgentSpec::named("frontend")).unwrap_err();
+        assert_eq!(
+            unknown.to_string(),
+            "configuration error: unknown agent 'frontend', expected one of architect, backend"
+        );
+
+        registry.register("frontend", || Box::new(AgentSolutionArchitect::new()));
+        assert_eq!(registry.names(), vec!["architect", "backend", "frontend"]);
+        assert!(registry.create(&AgentSpec::named("frontend")).is_ok());
+        assert!(registry
+            .check_pipeline(&[AgentSpec::named("backend"), AgentSpec::named("qa")])
+            .is_err());
+    }
+}
@@ -0,0 +1,260 @@
+use crate::models::{
+    agent_basic::basic_agent::AgentState,
+    agent_manager::{factsheet_store::FactSheetStore, pipeline::AgentSpec},
+    agents::agent_traits::{FactSheet, FactSheetField},
+    general::api_schema::RouteObject,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct AgentProgress {
+    pub position: String,
+    pub state: AgentState,
+    pub bug_cont: u8,
+    pub bug_errors: Option<String>,
+    #[serde(default)]
+    pub api_contract: Option<Vec<RouteObject>>,
+    #[serde(default)]
+    pub lint_errors: Option<String>,
+    #[serde(default)]
+    pub lint_rounds: u8,
+    #[serde(default)]
+    pub escalations: usize,
+}
+
+impl AgentProgress {
+    // An agent in `state` with no bugs, lints or escalations recorded yet.
+    pub fn new(position: &str, state: AgentState) -> Self {
+        Self {
+            position: position.to_string(),
+            state,
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Checkpoint {
+    pub workspace: PathBuf,
+    pub factsheet: FactSheetStore,
+    pub agents: Vec<AgentProgress>,
+    #[serde(default)]
+    pub pipeline: Vec<AgentSpec>,
+}
+
+impl Checkpoint {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents: String = fs::read_to_string(path)?;
+        serde_json::from_str(&contents).map_err(io::Error::other)
+    }
+
+    pub fn agent_progress(&self, position: &str) -> Option<&AgentProgress> {
+        self.agents
+            .iter()
+            .find(|progress| progress.position == position)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Checkpointer {
+    path: PathBuf,
+    checkpoint: Arc<Mutex<Checkpoint>>,
+}
+
+impl Checkpointer {
+    pub fn new(path: PathBuf, checkpoint: Checkpoint) -> Self {
+        Self {
+            path,
+            checkpoint: Arc::new(Mutex::new(checkpoint)),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn agent_progress(&self) -> Vec<AgentProgress> {
+        let checkpoint = self.checkpoint.lock().expect("Checkpoint lock poisoned");
+        checkpoint.agents.clone()
+    }
+
+    pub fn factsheet_store(&self) -> FactSheetStore {
+        let checkpoint = self.checkpoint.lock().expect("Checkpoint lock poisoned");
+        checkpoint.factsheet.clone()
+    }
+
+    pub fn save_factsheet(&self, agent_position: &str, factsheet: &FactSheet) -> io::Result<()> {
+        let mut checkpoint = self.checkpoint.lock().expect("Checkpoint lock poisoned");
+        checkpoint.factsheet.commit(agent_position, factsheet);
+        self.write(&checkpoint)
+    }
+
+    // Takes only the `written` fields from the agent's copy of the factsheet.
+    pub fn save_progress(
+        &self,
+        progress: AgentProgress,
+        factsheet: &FactSheet,
+        written: &[FactSheetField],
+    ) -> io::Result<()> {
+        let mut checkpoint = self.checkpoint.lock().expect("Checkpoint lock poisoned");
+        let mut merged: FactSheet = checkpoint.factsheet.current().clone();
+        for field in written {
+            field.copy(factsheet, &mut merged);
+        }
+        checkpoint.factsheet.commit(&progress.position, &merged);
+
+        match checkpoint
+            .agents
+            .iter_mut()
+            .find(|saved| saved.position == progress.position)
+        {
+            Some(saved) => *saved = progress,
+            None => checkpoint.agents.push(progress),
+        }
+
+        self.write(&checkpoint)
+    }
+
+    // Writes to a temporary file first so a crash never leaves a half written checkpoint.
+    fn write(&self, checkpoint: &Checkpoint) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let contents: String =
+            serde_json::to_string_pretty(checkpoint).map_err(io::Error::other)?;
+        let tmp_path: PathBuf = self.path.with_extension("json.tmp");
+        fs::write(&tmp_path, contents)?;
+        fs::rename(tmp_path, &self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::workspace::new_run_id;
+    use std::env;
+
+    #[test]
+    fn tests_checkpoint_records_latest_progress_per_agent() {
+        let path: PathBuf = env::temp_dir()
+            .join(format!("checkpoint-{}", new_run_id()))
+            .join("checkpoint.json");
+
+        let mut factsheet: FactSheet =
+            FactSheet::described("build a website that tracks fitness progress");
+
+        let checkpointer: Checkpointer = Checkpointer::new(
+            path.clone(),
+            Checkpoint {
+                workspace: PathBuf::from("/tmp/workspace"),
+                factsheet: FactSheetStore::new("Project Manager", factsheet.clone()),
+                agents: vec![],
+                pipeline: vec![],
+            },
+        );
+        checkpointer
+            .save_factsheet("Project Manager", &factsheet)
+            .unwrap();
+
+        let progress: AgentProgress = AgentProgress::new("Backend Developer", AgentState::Working);
+        checkpointer
+            .save_progress(
+                progress.clone(),
+                &factsheet,
+                &[FactSheetField::ExternalUrls],
+            )
+            .unwrap();
+
+        factsheet.external_urls = Some(vec!["http://worldtimeapi.org/api/timezone".to_string()]);
+        let failed_build: AgentProgress = AgentProgress {
+            state: AgentState::UnitTesting,
+            bug_cont: 1,
+            bug_errors: Some("error[E0425]: cannot find value `x`".to_string()),
+            ..progress
+        };
+        checkpointer
+            .save_progress(
+                failed_build.clone(),
+                &factsheet,
+                &[FactSheetField::ExternalUrls],
+            )
+            .unwrap();
+
+        let checkpoint: Checkpoint = Checkpoint::load(&path).unwrap();
+        assert_eq!(checkpoint.factsheet.current(), &factsheet);
+        assert_eq!(
+            checkpoint
+                .factsheet
+                .last_change_to("external_urls")
+                .unwrap()
+                .agent_position,
+            "Backend Developer"
+        );
+        assert_eq!(checkpoint.agents, vec![failed_build.clone()]);
+        assert_eq!(
+            checkpoint.agent_progress("Backend Developer"),
+            Some(&failed_build)
+        );
+        assert_eq!(checkpoint.agent_progress("Solutions Architect"), None);
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn tests_checkpoint_keeps_fields_other_agents_wrote() {
+        let path: PathBuf = env::temp_dir()
+            .join(format!("checkpoint-{}", new_run_id()))
+            .join("checkpoint.json");
+        let factsheet: FactSheet =
+            FactSheet::described("build a website that tracks fitness progress");
+        let checkpointer: Checkpointer = Checkpointer::new(
+            path.clone(),
+            Checkpoint {
+                workspace: PathBuf::from("/tmp/workspace"),
+                factsheet: FactSheetStore::new("Project Manager", factsheet.clone()),
+                agents: vec![],
+                pipeline: vec![],
+            },
+        );
+        let progress = |position: &str| AgentProgress::new(position, AgentState::Working);
+
+        let mut scoping: FactSheet = factsheet.clone();
+        scoping.external_urls = Some(vec!["http://worldtimeapi.org/api/timezone".to_string()]);
+        checkpointer
+            .save_progress(
+                progress("Solutions Architect"),
+                &scoping,
+                &[FactSheetField::ExternalUrls],
+            )
+            .unwrap();
+
+        // Started from the factsheet before the architect's change, so it has no urls.
+        let mut drafting: FactSheet = factsheet.clone();
+        drafting.api_endpoint_schema = Some(vec![]);
+        checkpointer
+            .save_progress(
+                progress("Backend Developer"),
+                &drafting,
+                &[FactSheetField::ApiEndpointSchema],
+            )
+            .unwrap();
+
+        let checkpoint: Checkpoint = Checkpoint::load(&path).unwrap();
+        assert_eq!(
+            checkpoint.factsheet.current().external_urls,
+            scoping.external_urls
+        );
+        assert_eq!(
+            checkpoint.factsheet.current().api_endpoint_schema,
+            Some(vec![])
+        );
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -0,0 +1,221 @@
+use crate::models::agents::agent_traits::FactSheet;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Value,
+    pub after: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FactSheetRevision {
+    pub revision: usize,
+    pub agent_position: String,
+    pub timestamp_ms: u64,
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FactSheetStore {
+    current: FactSheet,
+    revisions: Vec<FactSheetRevision>,
+}
+
+impl FactSheetStore {
+    pub fn new(agent_position: &str, initial: FactSheet) -> Self {
+        let empty: Value = match to_value(&initial) {
+            Value::Object(fields) => Value::Object(
+                fields
+                    .keys()
+                    .map(|field| (field.clone(), Value::Null))
+                    .collect::<Map<String, Value>>(),
+            ),
+            _ => Value::Null,
+        };
+
+        let mut changes: Vec<FieldChange> = vec![];
+        diff_values("", &empty, &to_value(&initial), &mut changes);
+
+        Self {
+            current: initial,
+            revisions: vec![FactSheetRevision {
+                revision: 0,
+                agent_position: agent_position.to_string(),
+                timestamp_ms: now_ms(),
+                changes,
+            }],
+        }
+    }
+
+    pub fn current(&self) -> &FactSheet {
+        &self.current
+    }
+
+    pub fn revisions(&self) -> &[FactSheetRevision] {
+        &self.revisions
+    }
+
+    // Records the difference to the last committed factsheet. Returns None when nothing changed.
+    pub fn commit(
+        &mut self,
+        agent_position: &str,
+        factsheet: &FactSheet,
+    ) -> Option<&FactSheetRevision> {
+        let changes: Vec<FieldChange> = diff_factsheets(&self.current, factsheet);
+        if changes.is_empty() {
+            return None;
+        }
+
+        self.current = factsheet.clone();
+        self.revisions.push(FactSheetRevision {
+            revision: self.revisions.len(),
+            agent_position: agent_position.to_string(),
+            timestamp_ms: now_ms(),
+            changes,
+        });
+        self.revisions.last()
+    }
+
+    // Finds the latest revision that touched `field` or anything nested under it.
+    pub fn last_change_to(&self, field: &str) -> Option<&FactSheetRevision> {
+        let nested_prefix: String = format!("{}.", field);
+        self.revisions.iter().rev().find(|revision| {
+            revision
+                .changes
+                .iter()
+                .any(|change| change.field == field || change.field.starts_with(&nested_prefix))
+        })
+    }
+}
+
+pub fn diff_factsheets(before: &FactSheet, after: &FactSheet) -> Vec<FieldChange> {
+    let mut changes: Vec<FieldChange> = vec![];
+    diff_values("", &to_value(before), &to_value(after), &mut changes);
+    changes
+}
+
+// Objects are compared key by key so e.g. a single file in `backend_code` shows up as
+// `backend_code.src/main.rs`. Everything else is compared as a whole value.
+fn diff_values(field: &str, before: &Value, after: &Value, changes: &mut Vec<FieldChange>) {
+    if before == after {
+        return;
+    }
+
+    match (before, after) {
+        (Value::Object(before_fields), Value::Object(after_fields)) => {
+            let mut keys: Vec<&String> = before_fields.keys().chain(after_fields.keys()).collect();
+            keys.sort();
+            keys.dedup();
+
+            for key in keys {
+                let nested_field: String = if field.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", field, key)
+                };
+                diff_values(
+                    &nested_field,
+                    before_fields.get(key).unwrap_or(&Value::Null),
+                    after_fields.get(key).unwrap_or(&Value::Null),
+                    changes,
+                );
+            }
+        }
+        _ => changes.push(FieldChange {
+            field: field.to_string(),
+            before: before.clone(),
+            after: after.clone(),
+        }),
+    }
+}
+
+fn to_value(factsheet: &FactSheet) -> Value {
+    serde_json::to_value(factsheet).expect("FactSheet is always serializable")
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{agents::agent_traits::ProjectScope, general::project_files::ProjectFiles};
+    use serde_json::json;
+
+    #[test]
+    fn tests_store_records_field_level_changes_per_agent() {
+        let mut store: FactSheetStore = FactSheetStore::new(
+            "Project Manager",
+            FactSheet::described("build a website that tracks fitness progress"),
+        );
+        assert_eq!(store.revisions().len(), 1);
+        assert_eq!(
+            store.revisions()[0].changes,
+            vec![FieldChange {
+                field: "project_description".to_string(),
+                before: Value::Null,
+                after: json!("build a website that tracks fitness progress"),
+            }]
+        );
+
+        let mut updated: FactSheet =
+            FactSheet::described("build a website that tracks fitness progress");
+        updated.project_scope = Some(ProjectScope {
+            is_crud_required: true,
+            is_user_login_and_logout: false,
+            is_external_urls_required: true,
+        });
+        updated.external_urls = Some(vec!["http://worldtimeapi.org/api/timezone".to_string()]);
+
+        let revision: &FactSheetRevision = store.commit("Solutions Architect", &updated).unwrap();
+        assert_eq!(revision.revision, 1);
+        let fields: Vec<&str> = revision
+            .changes
+            .iter()
+            .map(|change| change.field.as_str())
+            .collect();
+        assert_eq!(fields, vec!["external_urls", "project_scope"]);
+
+        assert!(store.commit("Backend Developer", &updated).is_none());
+        assert_eq!(store.revisions().len(), 2);
+
+        let mut backend_files: ProjectFiles = ProjectFiles::default();
+        backend_files
+            .insert("src/main.rs", "fn main() {}\n".to_string())
+            .unwrap();
+        updated.backend_code = Some(backend_files.clone());
+        store.commit("Backend Developer", &updated).unwrap();
+
+        backend_files
+            .insert("src/db.rs", "pub fn db() {}\n".to_string())
+            .unwrap();
+        updated.backend_code = Some(backend_files);
+        let revision: &FactSheetRevision = store.commit("Backend Developer", &updated).unwrap();
+        assert_eq!(
+            revision.changes,
+            vec![FieldChange {
+                field: "backend_code.src/db.rs".to_string(),
+                before: Value::Null,
+                after: json!("pub fn db() {}\n"),
+            }]
+        );
+
+        assert_eq!(store.current(), &updated);
+        assert_eq!(
+            store
+                .last_change_to("project_scope")
+                .unwrap()
+                .agent_position,
+            "Solutions Architect"
+        );
+        assert_eq!(store.last_change_to("backend_code").unwrap().revision, 3);
+        assert!(store.last_change_to("api_endpoint_schema").is_none());
+    }
+}
@@ -1,93 +1,327 @@
 use crate::{
     ai_functions::aifunc_managing::convert_user_input_to_goal,
-    helpers::general::ai_task_request,
+    helpers::{
+        command_line::PrintCommand,
+        general::{
+            ai_task_request, read_build_diagnostics, read_candidate_scores, read_style_reports,
+        },
+        workspace::Workspace,
+    },
     models::{
         agent_basic::basic_agent::{AgentState, BasicAgent},
+        agent_manager::{
+            agent_graph::AgentGraph,
+            agent_registry::AgentRegistry,
+            checkpoint::{Checkpoint, Checkpointer},
+            factsheet_store::FactSheetStore,
+            pipeline::{default_pipeline, AgentSpec},
+            project_summary::ProjectSummary,
+            run_report::RunReport,
+        },
         agents::{
-            agent_architect::AgentSolutionArchitect,
+            agent_context::{AgentContext, CancellationToken},
             agent_traits::{FactSheet, SpecialFunctions},
-            agent_backend::AgentBackendDeveloper,
         },
+        general::{api_schema::RouteObject, error::AutoGptError},
     },
 };
-use crossterm::style::Attribute;
-use reqwest::redirect::Attempt;
+use std::path::Path;
 
 #[derive(Debug)]
 pub struct ManagingAgent {
     attributes: BasicAgent,
     factsheet: FactSheet,
     agents: Vec<Box<dyn SpecialFunctions>>,
+    pipeline: Vec<AgentSpec>,
+    context: AgentContext,
+    checkpointer: Checkpointer,
 }
 
 impl ManagingAgent {
-    pub async fn new(usr_req: String) -> Result<Self, Box<dyn std::error::Error>> {
-        let position: String = "Project Manager".to_string();
-        let attributes: BasicAgent = BasicAgent {
+    fn manager_attributes() -> BasicAgent {
+        BasicAgent {
             objective: "Manage agents who are building and excellent website for the user"
                 .to_string(),
-            position: position.clone(),
+            position: "Project Manager".to_string(),
             state: AgentState::Discovery,
             memory: vec![],
-        };
+        }
+    }
+
+    // An `api_contract` fixes the routes up front; the backend must implement exactly those.
+    // The `pipeline` names agents from the `registry`, which run as soon as their inputs are ready.
+    pub async fn new(
+        usr_req: String,
+        api_contract: Option<Vec<RouteObject>>,
+        registry: &AgentRegistry,
+        pipeline: Vec<AgentSpec>,
+        workspace: Workspace,
+        cancellation: CancellationToken,
+    ) -> Result<Self, AutoGptError> {
+        let attributes: BasicAgent = Self::manager_attributes();
+        let position: String = attributes.position.clone();
+        let mut agents: Vec<Box<dyn SpecialFunctions>> = registry.create_pipeline(&pipeline)?;
+
+        let context: AgentContext = AgentContext::from_env(workspace.clone(), cancellation)?;
 
         let project_description: String = ai_task_request(
+            &context,
             usr_req,
             &position,
             get_function_string!(convert_user_input_to_goal),
             convert_user_input_to_goal,
         )
-        .await;
-
-        let agents: Vec<Box<dyn SpecialFunctions>> = vec![];
+        .await?;
 
         let factsheet: FactSheet = FactSheet {
             project_description,
             project_scope: None,
             external_urls: None,
             backend_code: None,
-            api_endpoint_schema: None,
+            api_endpoint_schema: api_contract,
         };
 
+        let checkpointer: Checkpointer = Checkpointer::new(
+            workspace.checkpoint_path(),
+            Checkpoint {
+                workspace: workspace.root().to_path_buf(),
+                factsheet: FactSheetStore::new(&position, factsheet.clone()),
+                agents: vec![],
+                pipeline: pipeline.clone(),
+            },
+        );
+        for agent in &mut agents {
+            agent.attach_checkpointer(checkpointer.clone());
+        }
+        checkpointer
+            .save_factsheet(&position, &factsheet)
+            .map_err(AutoGptError::io("save the checkpoint"))?;
+
+        let checkpoint_msg: String =
+            format!("Saving checkpoints to {}", checkpointer.path().display());
+        context.emit(PrintCommand::AICall, &position, &checkpoint_msg);
+
         Ok(Self {
             attributes,
             factsheet,
             agents,
+            pipeline,
+            context,
+            checkpointer,
         })
     }
 
-    fn add_agent(&mut self, agent: Box<dyn SpecialFunctions>) {
-        self.agents.push(agent);
-    }
+    // Runs the pipeline the checkpoint was started with; checkpoints from before pipelines were
+    // configurable get the default one.
+    pub fn resume(
+        checkpoint_path: &Path,
+        registry: &AgentRegistry,
+        cancellation: CancellationToken,
+    ) -> Result<Self, AutoGptError> {
+        let checkpoint: Checkpoint =
+            Checkpoint::load(checkpoint_path).map_err(AutoGptError::io("load the checkpoint"))?;
+        let pipeline: Vec<AgentSpec> = match checkpoint.pipeline.is_empty() {
+            true => default_pipeline(),
+            false => checkpoint.pipeline.clone(),
+        };
+        let mut agents: Vec<Box<dyn SpecialFunctions>> = registry.create_pipeline(&pipeline)?;
+        let context: AgentContext =
+            AgentContext::from_env(Workspace::open(&checkpoint.workspace), cancellation)?;
+        let checkpointer: Checkpointer =
+            Checkpointer::new(checkpoint_path.to_path_buf(), checkpoint.clone());
 
-    fn create_agent(&mut self) {
-        self.add_agent(Box::new(AgentSolutionArchitect::new()));
-        self.add_agent(Box::new(AgentBackendDeveloper::new()));
+        for agent in &mut agents {
+            agent.attach_checkpointer(checkpointer.clone());
+            let position: String = agent.get_attributes_from_agent().position.clone();
+            if let Some(progress) = checkpoint.agent_progress(&position) {
+                agent.restore_progress(progress);
+            }
+        }
+
+        Ok(Self {
+            attributes: Self::manager_attributes(),
+            factsheet: checkpoint.factsheet.current().clone(),
+            agents,
+            pipeline,
+            context,
+            checkpointer,
+        })
     }
 
-    pub async fn execute_project(&mut self) {
-        self.create_agent();
+    // Agent failures end up in the summary; only an unusable pipeline, an agent that could not be
+    // handed back or failing to write the report is an error here.
+    pub async fn execute_project(&mut self) -> Result<ProjectSummary, AutoGptError> {
+        let graph: AgentGraph = AgentGraph::new(&self.agents)?;
+        let contexts: Vec<AgentContext> = self
+            .pipeline
+            .iter()
+            .map(|spec| self.context.for_agent(spec))
+            .collect();
+        let summary: ProjectSummary = ProjectSummary {
+            agents: graph
+                .run(&mut self.agents, &contexts, &mut self.factsheet)
+                .await?,
+        };
 
-        for agent in &mut self.agents {
-            let agent_res: Result<(), Box<dyn std::error::Error>> =
-                agent.execute(&mut self.factsheet).await;
+        let report: RunReport = RunReport {
+            agent_progress: self.checkpointer.agent_progress(),
+            factsheet_history: self.checkpointer.factsheet_store(),
+            build_diagnostics: read_build_diagnostics(&self.context.workspace),
+            candidate_scores: read_candidate_scores(&self.context.workspace),
+            style_reports: read_style_reports(&self.context.workspace),
+        };
+        report
+            .save(&self.context.workspace.report_path())
+            .map_err(AutoGptError::io("save the run report"))?;
+        let report_msg: String = format!(
+            "Run report saved to {}",
+            self.context.workspace.report_path().display()
+        );
+        self.context
+            .emit(PrintCommand::AICall, &self.attributes.position, &report_msg);
 
-            let agent_info = agent.get_attributes_from_agent();
-            dbg!(agent_info);
-        }
+        Ok(summary)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        helpers::{project_history::ProjectHistory, workspace::new_run_id},
+        models::agent_manager::checkpoint::AgentProgress,
+    };
 
     #[tokio::test]
     async fn tests_managing_agent() {
         let usr_request: &str= "need a full stack app that fetches and tracks my fitness progress. Need to include timezone info from the web.";
-        let mut managing_agent: ManagingAgent = ManagingAgent::new(usr_request.to_string()).await.expect("Error creating Managing Agent");
+        let base_dir: std::path::PathBuf =
+            std::env::temp_dir().join(format!("managing-{}", new_run_id()));
+        let template_dir: std::path::PathBuf =
+            std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("web_gpt_template");
+        let workspace: Workspace = Workspace::create_in(&base_dir, &template_dir, "run").unwrap();
+        ProjectHistory::init(workspace.root()).unwrap();
 
-        managing_agent.execute_project().await;
+        let mut managing_agent: ManagingAgent = ManagingAgent::new(
+            usr_request.to_string(),
+            None,
+            &AgentRegistry::built_in(),
+            default_pipeline(),
+            workspace,
+            CancellationToken::default(),
+        )
+        .await
+        .expect("Error creating Managing Agent");
+
+        let summary: ProjectSummary = managing_agent
+            .execute_project()
+            .await
+            .expect("Error executing project");
+        assert_eq!(summary.exit_code(), 0);
         dbg!(managing_agent.factsheet);
+        let _ = std::fs::remove_dir_all(&base_dir);
+    }
+
+    #[test]
+    fn tests_resume_continues_from_first_unfinished_agent() {
+        let base_dir: std::path::PathBuf =
+            std::env::temp_dir().join(format!("resume-{}", new_run_id()));
+        let template_dir: std::path::PathBuf =
+            std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("web_gpt_template");
+        let workspace: Workspace = Workspace::create_in(&base_dir, &template_dir, "run").unwrap();
+
+        let factsheet: FactSheet =
+            FactSheet::described("build a website that tracks fitness progress");
+        let checkpointer: Checkpointer = Checkpointer::new(
+            workspace.checkpoint_path(),
+            Checkpoint {
+                workspace: workspace.root().to_path_buf(),
+                factsheet: FactSheetStore::new("Project Manager", factsheet.clone()),
+                agents: vec![],
+                pipeline: vec![],
+            },
+        );
+        let architect: AgentProgress =
+            AgentProgress::new("Solutions Architect", AgentState::Finished);
+        let backend: AgentProgress = AgentProgress {
+            bug_cont: 1,
+            bug_errors: Some("error[E0308]: mismatched types".to_string()),
+            ..AgentProgress::new("Backend Developer", AgentState::Working)
+        };
+        checkpointer
+            .save_progress(architect.clone(), &factsheet, &[])
+            .unwrap();
+        checkpointer
+            .save_progress(backend.clone(), &factsheet, &[])
+            .unwrap();
+
+        let managing_agent: ManagingAgent = ManagingAgent::resume(
+            &workspace.checkpoint_path(),
+            &AgentRegistry::built_in(),
+            CancellationToken::default(),
+        )
+        .unwrap();
+
+        assert_eq!(managing_agent.context.workspace, workspace);
+        assert_eq!(managing_agent.factsheet, factsheet);
+        let progress: Vec<AgentProgress> = managing_agent
+            .agents
+            .iter()
+            .map(|agent| agent.progress())
+            .collect();
+        assert_eq!(progress, vec![architect, backend]);
+
+        let _ = std::fs::remove_dir_all(&base_dir);
+    }
+
+    #[test]
+    fn tests_resume_runs_the_checkpointed_pipeline() {
+        let base_dir: std::path::PathBuf =
+            std::env::temp_dir().join(format!("resume-{}", new_run_id()));
+        let template_dir: std::path::PathBuf =
+            std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("web_gpt_template");
+        let workspace: Workspace = Workspace::create_in(&base_dir, &template_dir, "run").unwrap();
+
+        let factsheet: FactSheet = FactSheet {
+            api_endpoint_schema: Some(vec![]),
+            ..FactSheet::described("build a website that tracks fitness progress")
+        };
+        let backend_only: Vec<AgentSpec> = vec![AgentSpec {
+            model: Some("gpt-4o".to_string()),
+            max_fix_iterations: Some(5),
+            ..AgentSpec::named("backend")
+        }];
+        Checkpointer::new(
+            workspace.checkpoint_path(),
+            Checkpoint {
+                workspace: workspace.root().to_path_buf(),
+                factsheet: FactSheetStore::new("Project Manager", factsheet.clone()),
+                agents: vec![],
+                pipeline: backend_only.clone(),
+            },
+        )
+        .save_factsheet("Project Manager", &factsheet)
+        .unwrap();
+
+        let managing_agent: ManagingAgent = ManagingAgent::resume(
+            &workspace.checkpoint_path(),
+            &AgentRegistry::built_in(),
+            CancellationToken::default(),
+        )
+        .unwrap();
+
+        let positions: Vec<&str> = managing_agent
+            .agents
+            .iter()
+            .map(|agent| agent.get_attributes_from_agent().position.as_str())
+            .collect();
+        assert_eq!(positions, vec!["Backend Developer"]);
+        assert_eq!(managing_agent.pipeline, backend_only);
+
+        let backend_context: AgentContext = managing_agent.context.for_agent(&backend_only[0]);
+        assert_eq!(backend_context.config.model, "gpt-4o");
+        assert_eq!(backend_context.config.fix_policy.max_iterations, 5);
+
+        let _ = std::fs::remove_dir_all(&base_dir);
     }
 }
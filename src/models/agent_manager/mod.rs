@@ -1 +1,8 @@
-pub mod managing_agent;
\ No newline at end of file
+pub mod agent_graph;
+pub mod agent_registry;
+pub mod checkpoint;
+pub mod factsheet_store;
+pub mod managing_agent;
+pub mod pipeline;
+pub mod project_summary;
+pub mod run_report;
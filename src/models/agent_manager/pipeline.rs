@@ -0,0 +1,143 @@
+use crate::{helpers::command_line::get_cli_option, models::general::error::AutoGptError};
+use serde::{Deserialize, Serialize};
+use std::{env, fs};
+
+const DEFAULT_PIPELINE: [&str; 2] = ["architect", "backend"];
+
+// One agent of a pipeline, by its registry name, with the options it runs with. Unset options
+// fall back to the environment's configuration.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct AgentSpec {
+    pub agent: String,
+    pub objective: Option<String>,
+    pub model: Option<String>,
+    pub llm_retries: Option<u8>,
+    pub max_fix_iterations: Option<u8>,
+}
+
+impl AgentSpec {
+    pub fn named(agent: &str) -> Self {
+        Self {
+            agent: agent.to_string(),
+            objective: None,
+            model: None,
+            llm_retries: None,
+            max_fix_iterations: None,
+        }
+    }
+}
+
+pub fn default_pipeline() -> Vec<AgentSpec> {
+    DEFAULT_PIPELINE
+        .iter()
+        .map(|agent| AgentSpec::named(agent))
+        .collect()
+}
+
+// The agents to run, in order: a YAML list of specs from `--pipeline` or AGENT_PIPELINE_FILE, or
+// comma separated names from `--agents` or AGENT_PIPELINE, or the architect then the backend.
+pub fn pipeline_from_cli_and_env() -> Result<Vec<AgentSpec>, AutoGptError> {
+    if let Some(path) =
+        get_cli_option("--pipeline").or_else(|| env::var("AGENT_PIPELINE_FILE").ok())
+    {
+        let contents: String = fs::read_to_string(&path).map_err(|e| {
+            AutoGptError::Config(format!("could not read the pipeline {}: {}", path, e))
+        })?;
+        return parse_pipeline(&contents, &path);
+    }
+
+    match get_cli_option("--agents").or_else(|| env::var("AGENT_PIPELINE").ok()) {
+        Some(names) => pipeline_from_names(&names),
+        None => Ok(default_pipeline()),
+    }
+}
+
+pub fn parse_pipeline(contents: &str, source: &str) -> Result<Vec<AgentSpec>, AutoGptError> {
+    let pipeline: Vec<AgentSpec> = serde_yaml::from_str(contents)
+        .map_err(|e| AutoGptError::Config(format!("invalid pipeline in {}: {}", source, e)))?;
+    validate(pipeline)
+}
+
+pub fn pipeline_from_names(names: &str) -> Result<Vec<AgentSpec>, AutoGptError> {
+    validate(
+        names
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(AgentSpec::named)
+            .collect(),
+    )
+}
+
+// Agent names are checked against the registry when the agents are created.
+fn validate(pipeline: Vec<AgentSpec>) -> Result<Vec<AgentSpec>, AutoGptError> {
+    if pipeline.is_empty() {
+        return Err(AutoGptError::Config(
+            "the pipeline has no agents".to_string(),
+        ));
+    }
+    for (index, spec) in pipeline.iter().enumerate() {
+        if pipeline[..index]
+            .iter()
+            .any(|earlier| earlier.agent == spec.agent)
+        {
+            return Err(AutoGptError::Config(format!(
+                "agent '{}' is in the pipeline twice",
+                spec.agent
+            )));
+        }
+        if spec.max_fix_iterations == Some(0) {
+            return Err(AutoGptError::Config(format!(
+                "max_fix_iterations of agent '{}' must be a positive number",
+                spec.agent
+            )));
+        }
+    }
+    Ok(pipeline)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tests_pipeline_is_read_from_yaml_or_names() {
+        let pipeline: Vec<AgentSpec> = parse_pipeline(
+            "
+- agent: backend
+  objective: Build only the REST API, no frontend
+  model: gpt-4o
+  max_fix_iterations: 5
+- agent: architect
+",
+            "pipeline.yaml",
+        )
+        .unwrap();
+        assert_eq!(
+            pipeline,
+            vec![
+                AgentSpec {
+                    objective: Some("Build only the REST API, no frontend".to_string()),
+                    model: Some("gpt-4o".to_string()),
+                    max_fix_iterations: Some(5),
+                    ..AgentSpec::named("backend")
+                },
+                AgentSpec::named("architect"),
+            ]
+        );
+
+        assert_eq!(
+            pipeline_from_names(" architect ,").unwrap(),
+            vec![AgentSpec::named("architect")]
+        );
+        assert!(pipeline_from_names("").is_err());
+        assert!(pipeline_from_names("backend,backend").is_err());
+        assert!(parse_pipeline("- agent: backend\n  retries: 2\n", "pipeline.yaml").is_err());
+        assert!(parse_pipeline(
+            "- agent: backend\n  max_fix_iterations: 0\n",
+            "pipeline.yaml"
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,67 @@
+use crate::models::general::error::AutoGptError;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum AgentOutcome {
+    Finished,
+    Failed(AutoGptError),
+    NotRun,
+}
+
+// What happened to each agent of a project, in pipeline order.
+#[derive(Debug, Default)]
+pub struct ProjectSummary {
+    pub agents: Vec<(String, AgentOutcome)>,
+}
+
+impl ProjectSummary {
+    pub fn error(&self) -> Option<&AutoGptError> {
+        self.agents.iter().find_map(|(_, outcome)| match outcome {
+            AgentOutcome::Failed(e) => Some(e),
+            _ => None,
+        })
+    }
+
+    pub fn exit_code(&self) -> u8 {
+        self.error().map(AutoGptError::exit_code).unwrap_or(0)
+    }
+}
+
+impl fmt::Display for ProjectSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (position, outcome) in &self.agents {
+            match outcome {
+                AgentOutcome::Finished => writeln!(f, "{}: finished", position)?,
+                AgentOutcome::Failed(e) => writeln!(f, "{}: {}", position, e)?,
+                AgentOutcome::NotRun => writeln!(f, "{}: not run", position)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tests_summary_exits_with_the_first_failure() {
+        let mut summary: ProjectSummary = ProjectSummary {
+            agents: vec![("Solutions Architect".to_string(), AgentOutcome::Finished)],
+        };
+        assert_eq!(summary.exit_code(), 0);
+
+        summary.agents.push((
+            "Backend Developer".to_string(),
+            AgentOutcome::Failed(AutoGptError::Aborted),
+        ));
+        summary
+            .agents
+            .push(("Frontend Developer".to_string(), AgentOutcome::NotRun));
+        assert_eq!(summary.exit_code(), 5);
+        assert_eq!(
+            summary.to_string(),
+            "Solutions Architect: finished\nBackend Developer: stopped before running the generated code\nFrontend Developer: not run\n"
+        );
+    }
+}
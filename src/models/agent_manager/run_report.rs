@@ -0,0 +1,274 @@
+use crate::models::{
+    agent_basic::basic_agent::AgentState,
+    agent_manager::{
+        checkpoint::AgentProgress,
+        factsheet_store::{FactSheetRevision, FactSheetStore},
+    },
+    general::{
+        candidate_score::CandidateScore,
+        diagnostics::{BuildDiagnostics, DiagnosticLevel, StyleReport},
+    },
+};
+use serde_json::Value;
+use std::{fs, io, path::Path};
+
+const MAX_VALUE_CHARS: usize = 120;
+const FACTSHEET_FIELDS: [&str; 5] = [
+    "project_description",
+    "project_scope",
+    "external_urls",
+    "backend_code",
+    "api_endpoint_schema",
+];
+
+#[derive(Debug)]
+pub struct RunReport {
+    pub agent_progress: Vec<AgentProgress>,
+    pub factsheet_history: FactSheetStore,
+    pub build_diagnostics: Vec<BuildDiagnostics>,
+    pub candidate_scores: Vec<CandidateScore>,
+    pub style_reports: Vec<StyleReport>,
+}
+
+impl RunReport {
+    pub fn render(&self) -> String {
+        let mut report: String = String::from("# Run report\n\n");
+
+        if !self.agent_progress.is_empty() {
+            report.push_str(
+                "## Agents\n\n| Agent | State | Fix attempts | Escalations |\n|---|---|---|---|\n",
+            );
+            for progress in &self.agent_progress {
+                report.push_str(&format!(
+                    "| {} | {:?} | {} | {} |\n",
+                    progress.position, progress.state, progress.bug_cont, progress.escalations
+                ));
+            }
+            for progress in &self.agent_progress {
+                if progress.state != AgentState::Failed {
+                    continue;
+                }
+                if let Some(bug_errors) = &progress.bug_errors {
+                    report.push_str(&format!(
+                        "\n### {} last errors\n\n```\n{}\n```\n",
+                        progress.position,
+                        bug_errors.trim_end()
+                    ));
+                }
+            }
+            report.push('\n');
+        }
+
+        report.push_str(
+            "## FactSheet fields\n\n| Field | Last changed by | Revision |\n|---|---|---|\n",
+        );
+        for field in FACTSHEET_FIELDS {
+            match self.factsheet_history.last_change_to(field) {
+                Some(revision) => report.push_str(&format!(
+                    "| `{}` | {} | {} |\n",
+                    field, revision.agent_position, revision.revision
+                )),
+                None => report.push_str(&format!("| `{}` | - | - |\n", field)),
+            }
+        }
+
+        report.push_str("\n## FactSheet history\n");
+        let revisions: &[FactSheetRevision] = self.factsheet_history.revisions();
+        let start_ms: u64 = revisions
+            .first()
+            .map(|revision| revision.timestamp_ms)
+            .unwrap_or_default();
+
+        for revision in revisions {
+            report.push_str(&format!(
+                "\n### Revision {}: {} (+{:.1}s)\n\n",
+                revision.revision,
+                revision.agent_position,
+                revision.timestamp_ms.saturating_sub(start_ms) as f64 / 1000.0
+            ));
+            for change in &revision.changes {
+                report.push_str(&format!(
+                    "- `{}`: {} -> {}\n",
+                    change.field,
+                    summarize_value(&change.before),
+                    summarize_value(&change.after)
+                ));
+            }
+        }
+
+        if !self.candidate_scores.is_empty() {
+            report.push_str(
+                "\n## Backend candidates\n\n| Candidate | Compiles | Errors | Warnings | Endpoints passing | Scope routes | Chosen |\n|---|---|---|---|---|---|---|\n",
+            );
+            for score in &self.candidate_scores {
+                if let Some(note) = &score.note {
+                    report.push_str(&format!(
+                        "| {} | - | - | - | - | - | {} |\n",
+                        score.candidate, note
+                    ));
+                    continue;
+                }
+                report.push_str(&format!(
+                    "| {} | {} | {} | {} | {}/{} | {}/{} | {} |\n",
+                    score.candidate,
+                    if score.compiles { "yes" } else { "no" },
+                    score.errors,
+                    score.warnings,
+                    score.endpoints_passed,
+                    score.endpoints_tested,
+                    score.scope_matched,
+                    score.scope_checked,
+                    if score.chosen { "yes" } else { "" }
+                ));
+            }
+        }
+
+        if !self.build_diagnostics.is_empty() {
+            report.push_str(
+                "\n## Build diagnostics\n\n| Build | Errors | Warnings |\n|---|---|---|\n",
+            );
+            for build in &self.build_diagnostics {
+                report.push_str(&format!(
+                    "| {} | {} | {} |\n",
+                    build.build,
+                    build.count(DiagnosticLevel::Error),
+                    build.count(DiagnosticLevel::Warning)
+                ));
+            }
+            for build in &self.build_diagnostics {
+                report.push_str(&format!("\n### Build {}\n\n", build.build));
+                for diagnostic in &build.diagnostics {
+                    report.push_str(&format!(
+                        "- `{}:{}:{}` {}\n",
+                        diagnostic.file, diagnostic.line, diagnostic.column, diagnostic
+                    ));
+                }
+            }
+        }
+
+        if let Some(last_style_report) = self.style_reports.last() {
+            report.push_str(
+                "\n## Style checks\n\n| Round | Reformatted files | Denied lints | Warnings |\n|---|---|---|---|\n",
+            );
+            for style_report in &self.style_reports {
+                report.push_str(&format!(
+                    "| {} | {} | {} | {} |\n",
+                    style_report.round,
+                    style_report.reformatted_files.len(),
+                    style_report.count(DiagnosticLevel::Error),
+                    style_report.count(DiagnosticLevel::Warning)
+                ));
+            }
+
+            let warning_counts: Vec<(String, usize)> = last_style_report.warning_counts();
+            if !warning_counts.is_empty() {
+                report.push_str("\n### Remaining warnings\n\n| Lint | Count |\n|---|---|\n");
+                for (lint, count) in warning_counts {
+                    report.push_str(&format!("| `{}` | {} |\n", lint, count));
+                }
+            }
+        }
+
+        report
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.render())
+    }
+}
+
+fn summarize_value(value: &Value) -> String {
+    let rendered: String = value.to_string().replace('\n', " ");
+    if rendered.chars().count() <= MAX_VALUE_CHARS {
+        return format!("`{}`", rendered);
+    }
+
+    let truncated: String = rendered.chars().take(MAX_VALUE_CHARS).collect();
+    format!("`{}...` ({} chars)", truncated, rendered.chars().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{agents::agent_traits::FactSheet, general::diagnostics::Diagnostic};
+
+    #[test]
+    fn tests_report_shows_how_the_factsheet_evolved() {
+        let mut factsheet: FactSheet =
+            FactSheet::described("build a website that tracks fitness progress");
+        let mut store: FactSheetStore = FactSheetStore::new("Project Manager", factsheet.clone());
+        factsheet.external_urls = Some(vec!["x".repeat(200)]);
+        store.commit("Solutions Architect", &factsheet);
+
+        let report: String = RunReport {
+            agent_progress: vec![AgentProgress {
+                bug_errors: Some("error[E0308]: mismatched types".to_string()),
+                escalations: 3,
+                ..AgentProgress::new("Backend Developer", AgentState::Failed)
+            }],
+            factsheet_history: store,
+            build_diagnostics: vec![BuildDiagnostics {
+                build: 1,
+                diagnostics: vec![Diagnostic {
+                    level: DiagnosticLevel::Error,
+                    code: Some("E0425".to_string()),
+                    message: "cannot find value `x` in this scope".to_string(),
+                    file: "src/main.rs".to_string(),
+                    line: 3,
+                    column: 9,
+                    label: None,
+                    notes: vec![],
+                }],
+            }],
+            candidate_scores: vec![
+                CandidateScore {
+                    candidate: 1,
+                    compiles: true,
+                    warnings: 2,
+                    endpoints_passed: 3,
+                    endpoints_tested: 4,
+                    scope_matched: 4,
+                    scope_checked: 4,
+                    chosen: true,
+                    ..CandidateScore::default()
+                },
+                CandidateScore::unusable(2, "code unusable: empty manifest".to_string()),
+            ],
+            style_reports: vec![StyleReport {
+                round: 1,
+                reformatted_files: vec!["src/main.rs".to_string()],
+                lints: vec![Diagnostic {
+                    level: DiagnosticLevel::Warning,
+                    code: Some("clippy::needless_return".to_string()),
+                    message: "unneeded `return` statement".to_string(),
+                    file: "src/main.rs".to_string(),
+                    line: 7,
+                    column: 5,
+                    label: None,
+                    notes: vec![],
+                }],
+            }],
+        }
+        .render();
+
+        assert!(report.contains("| Backend Developer | Failed | 0 | 3 |"));
+        assert!(report.contains(
+            "### Backend Developer last errors\n\n```\nerror[E0308]: mismatched types\n```"
+        ));
+        assert!(report.contains("| `external_urls` | Solutions Architect | 1 |"));
+        assert!(report.contains("| `backend_code` | - | - |"));
+        assert!(report.contains("### Revision 1: Solutions Architect"));
+        assert!(report.contains("- `external_urls`: `null` -> `[\"xxx"));
+        assert!(report.contains("...` (204 chars)"));
+        assert!(report.contains("| 1 | 1 | 0 |"));
+        assert!(report.contains("| 1 | yes | 0 | 2 | 3/4 | 4/4 | yes |"));
+        assert!(report.contains("| 2 | - | - | - | - | - | code unusable: empty manifest |"));
+        assert!(report.contains("| 1 | 1 | 0 | 1 |"));
+        assert!(report.contains("| `clippy::needless_return` | 1 |"));
+        assert!(report
+            .contains("- `src/main.rs:3:9` error[E0425]: cannot find value `x` in this scope"));
+    }
+}
@@ -9,17 +9,23 @@ use crate::{
             basic_agent::{AgentState, BasicAgent},
             basic_traits::BasicTraits,
         },
-        agents::agent_traits::{FactSheet, ProjectScope, SpecialFunctions},
+        agent_manager::checkpoint::{AgentProgress, Checkpointer},
+        agents::{
+            agent_context::AgentContext,
+            agent_traits::{
+                FactSheet, FactSheetField, FactSheetInput, ProjectScope, SpecialFunctions,
+            },
+        },
+        general::error::AutoGptError,
     },
 };
 
 use async_trait::async_trait;
-use reqwest::Client;
-use std::time::Duration;
 
 #[derive(Debug)]
 pub struct AgentSolutionArchitect {
     attributes: BasicAgent,
+    checkpointer: Option<Checkpointer>,
 }
 
 impl AgentSolutionArchitect {
@@ -31,43 +37,52 @@ impl AgentSolutionArchitect {
             state: AgentState::Discovery,
             memory: vec![],
         };
-        Self { attributes }
+        Self {
+            attributes,
+            checkpointer: None,
+        }
     }
 
-    async fn call_project_scope(&mut self, factsheet: &mut FactSheet) -> ProjectScope {
-        let msg_context: String = format!("{}", factsheet.project_description);
+    async fn call_project_scope(
+        &mut self,
+        context: &AgentContext,
+        factsheet: &mut FactSheet,
+    ) -> Result<ProjectScope, AutoGptError> {
+        let msg_context: String = factsheet.project_description.clone();
 
         let ai_response: ProjectScope = ai_task_request_decoded::<ProjectScope>(
+            context,
             msg_context,
             &self.attributes.position,
             get_function_string!(print_project_scope),
             print_project_scope,
         )
-        .await;
+        .await?;
 
-        factsheet.project_scope = Some(ai_response.clone());
+        factsheet.project_scope = Some(ai_response);
         self.attributes.update_state(AgentState::Finished);
 
-        return ai_response;
+        Ok(ai_response)
     }
 
     async fn call_ditermine_external_urls(
         &mut self,
+        context: &AgentContext,
         factsheet: &mut FactSheet,
         msg_context: String,
-    ) {
-        let msg_context: String = format!("{}", factsheet.project_description);
-
+    ) -> Result<(), AutoGptError> {
         let ai_response: Vec<String> = ai_task_request_decoded::<Vec<String>>(
+            context,
             msg_context,
             &self.attributes.position,
             get_function_string!(print_site_urls),
             print_site_urls,
         )
-        .await;
+        .await?;
 
         factsheet.external_urls = Some(ai_response);
         self.attributes.state = AgentState::UnitTesting;
+        Ok(())
     }
 }
 
@@ -77,59 +92,87 @@ impl SpecialFunctions for AgentSolutionArchitect {
         &self.attributes
     }
 
+    fn get_attributes_mut(&mut self) -> &mut BasicAgent {
+        &mut self.attributes
+    }
+
+    fn get_checkpointer(&self) -> Option<&Checkpointer> {
+        self.checkpointer.as_ref()
+    }
+
+    fn attach_checkpointer(&mut self, checkpointer: Checkpointer) {
+        self.checkpointer = Some(checkpointer);
+    }
+
+    fn progress(&self) -> AgentProgress {
+        AgentProgress::new(&self.attributes.position, self.attributes.state)
+    }
+
+    fn restore_progress(&mut self, progress: &AgentProgress) {
+        self.attributes.state = progress.state;
+    }
+
+    fn reads(&self) -> Vec<FactSheetInput> {
+        vec![FactSheetInput::Required(FactSheetField::ProjectDescription)]
+    }
+
+    fn writes(&self) -> Vec<FactSheetField> {
+        vec![FactSheetField::ProjectScope, FactSheetField::ExternalUrls]
+    }
+
     async fn execute(
         &mut self,
+        context: &AgentContext,
         factsheet: &mut FactSheet,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> Result<(), AutoGptError> {
         while self.attributes.state != AgentState::Finished {
+            self.save_checkpoint(context, factsheet);
+            context.cancellation.check()?;
+
             match self.attributes.state {
                 AgentState::Discovery => {
-                    let project_scope: ProjectScope = self.call_project_scope(factsheet).await;
+                    let project_scope: ProjectScope =
+                        self.call_project_scope(context, factsheet).await?;
                     if project_scope.is_external_urls_required {
                         self.call_ditermine_external_urls(
+                            context,
                             factsheet,
                             factsheet.project_description.clone(),
                         )
-                        .await;
+                        .await?;
                         self.attributes.state = AgentState::UnitTesting;
                     }
                 }
                 AgentState::UnitTesting => {
                     let mut exclude_urls: Vec<String> = vec![];
-                    let client: Client = Client::builder()
-                        .timeout(Duration::from_secs(5))
-                        .build()
-                        .unwrap();
-                    let urls: &Vec<String> = factsheet
-                        .external_urls
-                        .as_ref()
-                        .expect("No URL object on factsheet");
-
-                    for url in urls {
+                    let urls: Vec<String> = factsheet.external_urls.clone().unwrap_or_default();
+
+                    for url in &urls {
                         let endpoint_str: String = format!("Testing URL Endpoint: {}", url);
-                        PrintCommand::UnitTest.print_agent_message(
+                        context.emit(
+                            PrintCommand::UnitTest,
                             self.attributes.position.as_str(),
                             endpoint_str.as_str(),
                         );
 
-                        match check_status_code(&client, url).await {
+                        match check_status_code(&context.http_client, url).await {
                             Ok(status_code) => {
                                 if status_code != 200 {
                                     exclude_urls.push(url.clone())
                                 }
                             }
-                            Err(e) => println!("Error checking {}: {}", url, e),
+                            Err(e) => context.emit(
+                                PrintCommand::Issue,
+                                self.attributes.position.as_str(),
+                                &format!("Error checking {}: {}", url, e),
+                            ),
                         }
                     }
 
-                    if exclude_urls.len() > 0 {
-                        let new_urls: Vec<String> = factsheet
-                            .external_urls
-                            .as_ref()
-                            .unwrap()
-                            .iter()
-                            .filter(|url| !exclude_urls.contains(&url))
-                            .cloned()
+                    if !exclude_urls.is_empty() {
+                        let new_urls: Vec<String> = urls
+                            .into_iter()
+                            .filter(|url| !exclude_urls.contains(url))
                             .collect();
                         factsheet.external_urls = Some(new_urls);
                     }
@@ -141,6 +184,8 @@ impl SpecialFunctions for AgentSolutionArchitect {
                 }
             }
         }
+
+        self.save_checkpoint(context, factsheet);
         Ok(())
     }
 }
@@ -148,6 +193,118 @@ impl SpecialFunctions for AgentSolutionArchitect {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        apis::llm_provider::LlmProvider,
+        helpers::workspace::{new_run_id, Workspace},
+        models::{
+            agents::agent_context::{
+                AgentConfig, AgentEvent, CancellationToken, EventSink, TerminalUser,
+            },
+            general::llm::Message,
+        },
+    };
+    use reqwest::Client;
+    use std::{
+        env, fs,
+        path::{Path, PathBuf},
+        sync::{Arc, Mutex},
+    };
+
+    #[derive(Debug)]
+    struct ScriptedProvider {
+        response: String,
+    }
+
+    #[async_trait]
+    impl LlmProvider for ScriptedProvider {
+        async fn chat(
+            &self,
+            _messages: Vec<Message>,
+            _model: &str,
+        ) -> Result<String, AutoGptError> {
+            Ok(self.response.clone())
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct RecordedEvents {
+        events: Mutex<Vec<AgentEvent>>,
+    }
+
+    impl EventSink for RecordedEvents {
+        fn emit(&self, event: AgentEvent) {
+            self.events.lock().unwrap().push(event);
+        }
+    }
+
+    // Everything the context needs on disk goes under `base_dir`.
+    fn scripted_context(
+        base_dir: &Path,
+        response: &str,
+        events: Arc<RecordedEvents>,
+    ) -> AgentContext {
+        let template_dir: PathBuf = base_dir.join("template");
+        fs::create_dir_all(template_dir.join("src")).unwrap();
+        let workspace: Workspace =
+            Workspace::create_in(&base_dir.join("workspaces"), &template_dir, "run").unwrap();
+
+        AgentContext {
+            provider: Arc::new(ScriptedProvider {
+                response: response.to_string(),
+            }),
+            http_client: Client::new(),
+            config: AgentConfig::default(),
+            workspace,
+            events,
+            user: Arc::new(TerminalUser),
+            cancellation: CancellationToken::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn tests_architect_runs_against_a_scripted_provider() {
+        let events: Arc<RecordedEvents> = Arc::new(RecordedEvents::default());
+        let base_dir: PathBuf = env::temp_dir().join(format!("architect-{}", new_run_id()));
+        let context: AgentContext = scripted_context(
+            &base_dir,
+            r#"{"is_crud_required":true,"is_user_login_and_logout":false,"is_external_urls_required":false}"#,
+            events.clone(),
+        );
+        let mut agent: AgentSolutionArchitect = AgentSolutionArchitect::new();
+        let mut factsheet: FactSheet = FactSheet::described("Build a todo list api");
+
+        agent.execute(&context, &mut factsheet).await.unwrap();
+
+        assert_eq!(agent.attributes.state, AgentState::Finished);
+        assert!(factsheet.project_scope.unwrap().is_crud_required);
+        assert!(factsheet.external_urls.is_none());
+        assert!(events
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|event| event.kind == PrintCommand::AICall));
+
+        let _ = fs::remove_dir_all(&base_dir);
+    }
+
+    #[tokio::test]
+    async fn tests_architect_stops_when_cancelled() {
+        let events: Arc<RecordedEvents> = Arc::new(RecordedEvents::default());
+        let base_dir: PathBuf = env::temp_dir().join(format!("architect-{}", new_run_id()));
+        let context: AgentContext = scripted_context(&base_dir, "{}", events.clone());
+        context.cancellation.cancel();
+        let mut agent: AgentSolutionArchitect = AgentSolutionArchitect::new();
+        let mut factsheet: FactSheet = FactSheet::described("Build a todo list api");
+
+        let result: Result<(), AutoGptError> = agent.execute(&context, &mut factsheet).await;
+
+        assert!(matches!(result, Err(AutoGptError::Cancelled)));
+        assert!(factsheet.project_scope.is_none());
+        assert!(events.events.lock().unwrap().is_empty());
+
+        let _ = fs::remove_dir_all(&base_dir);
+    }
 
     #[tokio::test]
     async fn tests_solution_architect() {
@@ -161,14 +318,23 @@ mod tests {
             api_endpoint_schema: None,
         };
 
+        let base_dir: PathBuf = env::temp_dir().join(format!("architect-{}", new_run_id()));
+        let template_dir: PathBuf =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("web_gpt_template");
+        let workspace: Workspace = Workspace::create_in(&base_dir, &template_dir, "run")
+            .expect("Unable to create a workspace.");
+        let context: AgentContext = AgentContext::from_env(workspace, CancellationToken::default())
+            .expect("Unable to configure the agent context.");
+
         agent
-            .execute(&mut factsheet)
+            .execute(&context, &mut factsheet)
             .await
             .expect("Unable to execute Solutions Architect Agent.");
 
-        assert!(factsheet.project_scope != None);
+        assert!(factsheet.project_scope.is_some());
         assert!(factsheet.external_urls.is_some());
 
         dbg!(factsheet);
+        let _ = fs::remove_dir_all(&base_dir);
     }
 }
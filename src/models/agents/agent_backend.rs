@@ -1,35 +1,57 @@
 use crate::{
     ai_functions::aifunc_backend::{
-        print_backend_webserver_code, print_fixed_code, print_improved_webserver_code,
+        print_backend_file_plan, print_backend_webserver_code, print_fixed_code,
+        print_fixed_code_edits, print_improved_webserver_code, print_improved_webserver_edits,
         print_rest_api_endpoints,
     },
     helpers::{
-        command_line::{confirm_safe_code, PrintCommand},
+        code_edits::{apply_edits, parse_edits, EditMode},
+        command_line::PrintCommand,
         general::{
-            ai_task_request, check_status_code, read_code_template_contents,
-            read_exec_main_contents, save_api_endpoints, save_backend_code,
-            WEB_SERVER_PROJECT_PATH,
+            ai_task_request, ai_task_request_decoded, ai_task_request_with_model,
+            read_backend_files, read_code_template_contents, remove_backend_files, save_api_schema,
+            save_backend_files, save_candidate_scores,
         },
+        project_history::StepOutcome,
+        route_extraction::extract_routes,
+        test_bench::{BuildCheck, ServerCheck, TestBench},
+        workspace::Workspace,
     },
     models::{
         agent_basic::basic_agent::{AgentState, BasicAgent},
-        agents::agent_traits::{FactSheet, RouteObject, SpecialFunctions},
+        agent_manager::checkpoint::{AgentProgress, Checkpointer},
+        agents::{
+            agent_context::AgentContext,
+            agent_traits::{
+                FactSheet, FactSheetField, FactSheetInput, PlannedFile, ProjectScope,
+                SpecialFunctions,
+            },
+        },
+        general::{
+            api_schema::{diff_routes, RouteDiff, RouteObject},
+            bug_report::{BugKind, BugReport},
+            candidate_score::{best_candidate, scope_coverage, CandidateScore},
+            diagnostics::{errors_for_fix, DiagnosticLevel, StyleReport},
+            error::AutoGptError,
+            fix_policy::Escalation,
+            project_files::{ProjectFiles, MAIN_FILE},
+        },
     },
 };
 use async_trait::async_trait;
-use reqwest::Client;
-use std::{
-    fs,
-    process::{Command, Stdio},
-    time::Duration,
-};
-use tokio::time;
+use tokio::task::JoinHandle;
 
 #[derive(Debug)]
 pub struct AgentBackendDeveloper {
     attributes: BasicAgent,
     bug_errors: Option<String>,
     bug_cont: u8,
+    last_ai_function: &'static str,
+    checkpointer: Option<Checkpointer>,
+    api_contract: Option<Vec<RouteObject>>,
+    lint_errors: Option<String>,
+    lint_rounds: u8,
+    escalations: usize,
 }
 
 impl AgentBackendDeveloper {
@@ -45,258 +67,871 @@ impl AgentBackendDeveloper {
             attributes,
             bug_errors: None,
             bug_cont: 0,
+            last_ai_function: get_function_string!(print_backend_webserver_code),
+            checkpointer: None,
+            api_contract: None,
+            lint_errors: None,
+            lint_rounds: 0,
+            escalations: 0,
         }
     }
 
-    async fn call_initial_backend_code(&mut self, factsheet: &mut FactSheet) {
-        let code_template_str: String = read_code_template_contents();
+    fn api_contract_context(&self) -> String {
+        match &self.api_contract {
+            Some(api_contract) => format!(
+                "API_CONTRACT: {} \n",
+                serde_json::to_value(api_contract).unwrap_or_default()
+            ),
+            None => String::new(),
+        }
+    }
 
-        let mut msg_context: String = format!(
-            "CODE TEMPLATE: {} \n PROJECT_DESCRIPTION: {} \n",
-            code_template_str, factsheet.project_description
+    // Sends the errors to the next fix step, escalating once the fix policy's attempts run out.
+    fn record_bugs(
+        &mut self,
+        context: &AgentContext,
+        bug_report: BugReport,
+        factsheet: &mut FactSheet,
+    ) -> Result<(), AutoGptError> {
+        self.commit_step(
+            context,
+            match bug_report.kind {
+                BugKind::CompileErrors => StepOutcome::CompileErrors,
+                BugKind::ContractMismatch => StepOutcome::ContractMismatch,
+                BugKind::ServerCrashed | BugKind::ServerNotReady | BugKind::EndpointFailures => {
+                    StepOutcome::RuntimeErrors
+                }
+            },
         );
+        self.bug_cont += 1;
+        self.bug_errors = Some(bug_report.to_string());
+        self.attributes.state = AgentState::Working;
+
+        if self.bug_cont >= context.config.fix_policy.max_iterations {
+            if self.escalate(context, factsheet)? {
+                self.bug_cont = 0;
+            } else {
+                context.emit(
+                    PrintCommand::Issue,
+                    self.attributes.position.as_str(),
+                    "Backend Code Unit Testing: Too many bugs found in code, giving up.",
+                );
+                self.attributes.state = AgentState::Failed;
+            }
+        }
+        Ok(())
+    }
 
-        let ai_response: String = ai_task_request(
+    // Applies the next escalation that can be applied; false when none are left.
+    fn escalate(
+        &mut self,
+        context: &AgentContext,
+        factsheet: &mut FactSheet,
+    ) -> Result<bool, AutoGptError> {
+        while let Some(escalation) = context
+            .config
+            .fix_policy
+            .escalations
+            .get(self.escalations)
+            .copied()
+        {
+            self.escalations += 1;
+
+            let escalation_msg: String = match escalation {
+                Escalation::StrongerModel => format!(
+                    "Escalating: fixing with {} from now on",
+                    context.config.fix_policy.stronger_model
+                ),
+                Escalation::Regenerate => {
+                    // The schema holds the extracted routes by now; only a contract stays.
+                    factsheet.api_endpoint_schema = self.api_contract.clone();
+                    self.bug_errors = None;
+                    self.lint_errors = None;
+                    self.lint_rounds = 0;
+                    self.attributes.state = AgentState::Discovery;
+                    "Escalating: regenerating the code from scratch".to_string()
+                }
+                Escalation::Rollback => {
+                    let history = context.workspace.history();
+                    let revision: String = match history.last_good_revision() {
+                        Ok(Some(revision)) => revision,
+                        Ok(None) => {
+                            context.emit(
+                                PrintCommand::Issue,
+                                &self.attributes.position,
+                                "Cannot roll back: no revision has compiled yet",
+                            );
+                            continue;
+                        }
+                        Err(e) => {
+                            let err_msg: String = format!("Cannot roll back: {}", e);
+                            context.emit(PrintCommand::Issue, &self.attributes.position, &err_msg);
+                            continue;
+                        }
+                    };
+                    if let Err(e) = history.restore_revision(&revision) {
+                        let err_msg: String = format!("Cannot roll back: {}", e);
+                        context.emit(PrintCommand::Issue, &self.attributes.position, &err_msg);
+                        continue;
+                    }
+                    factsheet.backend_code = Some(read_backend_files(&context.workspace)?);
+                    self.bug_errors = None;
+                    self.lint_errors = None;
+                    self.attributes.state = AgentState::UnitTesting;
+                    format!(
+                        "Escalating: rolled back to the last compiling revision {}",
+                        revision
+                    )
+                }
+            };
+            context.emit(
+                PrintCommand::Issue,
+                &self.attributes.position,
+                &escalation_msg,
+            );
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    fn fix_model(&self, context: &AgentContext) -> String {
+        context
+            .config
+            .fix_policy
+            .fix_model(self.escalations)
+            .unwrap_or(&context.config.model)
+            .to_string()
+    }
+
+    fn commit_step(&self, context: &AgentContext, outcome: StepOutcome) {
+        if let Err(e) = context.workspace.history().commit_step(
+            &self.attributes.position,
+            self.last_ai_function,
+            outcome,
+        ) {
+            let err_msg: String = format!("Failed to record project history: {}", e);
+            context.emit(PrintCommand::Issue, &self.attributes.position, &err_msg);
+        }
+    }
+
+    fn replace_backend_files(
+        &self,
+        context: &AgentContext,
+        factsheet: &mut FactSheet,
+        backend_files: ProjectFiles,
+    ) -> Result<(), AutoGptError> {
+        if let Some(previous_files) = &factsheet.backend_code {
+            let new_paths: Vec<String> = backend_files.paths();
+            let stale_paths: Vec<String> = previous_files
+                .paths()
+                .into_iter()
+                .filter(|path| !new_paths.contains(path))
+                .collect();
+            remove_backend_files(&context.workspace, &stale_paths)?;
+        }
+
+        save_backend_files(&context.workspace, &backend_files)?;
+        factsheet.backend_code = Some(backend_files);
+        Ok(())
+    }
+
+    // Returns false when the edits could not be applied and a full rewrite is needed instead.
+    async fn call_backend_edits(
+        &mut self,
+        context: &AgentContext,
+        factsheet: &mut FactSheet,
+        msg_context: String,
+        function_name: &'static str,
+        function_pass: for<'a> fn(&'a str) -> &'static str,
+        model: &str,
+    ) -> Result<bool, AutoGptError> {
+        let ai_response: String = ai_task_request_with_model(
+            context,
             msg_context,
             &self.attributes.position,
-            get_function_string!(print_backend_webserver_code),
-            print_backend_webserver_code,
+            function_name,
+            function_pass,
+            model,
         )
-        .await;
+        .await?;
 
-        save_backend_code(&ai_response);
+        let mut backend_files: ProjectFiles = factsheet.backend_code.clone().unwrap_or_default();
 
-        factsheet.backend_code = Some(ai_response);
+        match parse_edits(&ai_response).and_then(|edits| apply_edits(&backend_files, &edits)) {
+            Ok(changed_files) => {
+                save_backend_files(&context.workspace, &changed_files)?;
+                backend_files.merge(changed_files);
+                factsheet.backend_code = Some(backend_files);
+                self.last_ai_function = function_name;
+                Ok(true)
+            }
+            Err(e) => {
+                let err_msg: String =
+                    format!("Patch failed to apply, falling back to full rewrite: {}", e);
+                context.emit(PrintCommand::Issue, &self.attributes.position, &err_msg);
+                Ok(false)
+            }
+        }
+    }
+
+    fn plan_context(
+        &self,
+        context: &AgentContext,
+        factsheet: &FactSheet,
+    ) -> Result<String, AutoGptError> {
+        Ok(format!(
+            "CODE TEMPLATE: {} \n PROJECT_DESCRIPTION: {} \n {}",
+            read_code_template_contents(&context.workspace)?,
+            factsheet.project_description,
+            self.api_contract_context()
+        ))
+    }
+
+    fn confirm_running_code(&self, context: &AgentContext) -> Result<(), AutoGptError> {
+        context.emit(
+            PrintCommand::UnitTest,
+            self.attributes.position.as_str(),
+            "Backend Code unit Testing: Requesting user input",
+        );
+        match context
+            .user
+            .confirm_safe_code()
+            .map_err(AutoGptError::io("read the confirmation"))?
+        {
+            true => Ok(()),
+            false => Err(AutoGptError::Aborted),
+        }
+    }
+
+    async fn call_initial_backend_code(
+        &mut self,
+        context: &AgentContext,
+        factsheet: &mut FactSheet,
+    ) -> Result<(), AutoGptError> {
+        let backend_files: ProjectFiles = draft_backend_files(
+            context.clone(),
+            self.attributes.position.clone(),
+            self.plan_context(context, factsheet)?,
+        )
+        .await?;
+
+        self.replace_backend_files(context, factsheet, backend_files)?;
+        self.last_ai_function = get_function_string!(print_backend_webserver_code);
+        Ok(())
+    }
+
+    // Drafts the server several times over, builds and tests every draft in its own workspace and
+    // carries on with the best one.
+    async fn call_candidate_backend_code(
+        &mut self,
+        context: &AgentContext,
+        factsheet: &mut FactSheet,
+    ) -> Result<(), AutoGptError> {
+        let plan_context: String = self.plan_context(context, factsheet)?;
+        let drafts: Vec<JoinHandle<Result<ProjectFiles, AutoGptError>>> =
+            (1..=context.config.candidates)
+                .map(|candidate| {
+                    let position: String =
+                        format!("{} (candidate {})", self.attributes.position, candidate);
+                    tokio::spawn(draft_backend_files(
+                        context.clone(),
+                        position,
+                        plan_context.clone(),
+                    ))
+                })
+                .collect();
+
+        let mut scores: Vec<CandidateScore> = vec![];
+        let mut written: Vec<(usize, Workspace, ProjectFiles)> = vec![];
+        for (candidate, draft) in (1..=context.config.candidates).zip(drafts) {
+            let backend_files: ProjectFiles = match draft.await {
+                Ok(Ok(backend_files)) => backend_files,
+                Ok(Err(e)) => {
+                    scores.push(CandidateScore::unusable(
+                        candidate,
+                        format!("draft failed: {}", e),
+                    ));
+                    continue;
+                }
+                Err(e) => {
+                    scores.push(CandidateScore::unusable(
+                        candidate,
+                        format!("generation failed: {}", e),
+                    ));
+                    continue;
+                }
+            };
+
+            let candidate_workspace: Workspace = context
+                .workspace
+                .create_candidate(candidate)
+                .map_err(AutoGptError::io("create a candidate workspace"))?;
+            save_backend_files(&candidate_workspace, &backend_files)?;
+            written.push((candidate, candidate_workspace, backend_files));
+        }
+
+        // Every draft is on disk before the user is asked, so all of them can be reviewed.
+        if !written.is_empty() {
+            for (candidate, candidate_workspace, _) in &written {
+                let written_msg: String = format!(
+                    "Backend candidate {} written to {}",
+                    candidate,
+                    candidate_workspace.root().display()
+                );
+                context.emit(
+                    PrintCommand::UnitTest,
+                    self.attributes.position.as_str(),
+                    &written_msg,
+                );
+            }
+            self.confirm_running_code(context)?;
+        }
+
+        let mut candidate_workspaces: Vec<(usize, Workspace)> = vec![];
+        let mut evaluations: Vec<(usize, JoinHandle<(CandidateScore, ProjectFiles)>)> = vec![];
+        for (candidate, candidate_workspace, backend_files) in written {
+            candidate_workspaces.push((candidate, candidate_workspace.clone()));
+            let position: String =
+                format!("{} (candidate {})", self.attributes.position, candidate);
+            let test_bench: TestBench = TestBench::new(candidate_workspace, &position, context);
+
+            evaluations.push((
+                candidate,
+                tokio::spawn(evaluate_candidate(
+                    candidate,
+                    test_bench,
+                    backend_files,
+                    self.api_contract.clone(),
+                    factsheet.project_scope,
+                )),
+            ));
+        }
+
+        let mut candidate_files: Vec<(usize, ProjectFiles)> = vec![];
+        for (candidate, evaluation) in evaluations {
+            match evaluation.await {
+                Ok((score, backend_files)) => {
+                    candidate_files.push((score.candidate, backend_files));
+                    scores.push(score);
+                }
+                Err(e) => scores.push(CandidateScore::unusable(
+                    candidate,
+                    format!("evaluation failed: {}", e),
+                )),
+            }
+        }
+        scores.sort_by_key(|score| score.candidate);
+
+        let chosen: Option<usize> = best_candidate(&scores);
+        for score in &mut scores {
+            score.chosen = Some(score.candidate) == chosen;
+            context.emit(
+                PrintCommand::UnitTest,
+                self.attributes.position.as_str(),
+                &score.to_string(),
+            );
+        }
+        save_candidate_scores(&context.workspace, &scores)?;
+
+        // Only the scores of the losing candidates are kept, their builds can be gigabytes.
+        for (candidate, candidate_workspace) in candidate_workspaces {
+            if Some(candidate) == chosen {
+                continue;
+            }
+            if let Err(e) = candidate_workspace.remove() {
+                let err_msg: String =
+                    format!("Could not remove backend candidate {}: {}", candidate, e);
+                context.emit(PrintCommand::Issue, &self.attributes.position, &err_msg);
+            }
+        }
+
+        let chosen: usize = chosen.ok_or_else(|| AutoGptError::AgentFailed {
+            position: self.attributes.position.clone(),
+            reason: "no backend candidate produced usable code".to_string(),
+        })?;
+
+        let chosen_msg: String = format!("Continuing with backend candidate {}", chosen);
+        context.emit(
+            PrintCommand::UnitTest,
+            self.attributes.position.as_str(),
+            &chosen_msg,
+        );
+
+        // Only evaluated candidates can be chosen, and every one of them has files.
+        if let Some((_, backend_files)) = candidate_files
+            .into_iter()
+            .find(|(candidate, _)| *candidate == chosen)
+        {
+            self.replace_backend_files(context, factsheet, backend_files)?;
+        }
+        self.last_ai_function = get_function_string!(print_backend_webserver_code);
+        Ok(())
     }
 
-    async fn call_improved_backend_code(&mut self, factsheet: &mut FactSheet) {
-        let mut msg_context: String = format!(
-            "CODE TEMPLATE: {:?} \n PROJECT_DESCRIPTION: {:?} \n",
-            factsheet.backend_code, factsheet
+    async fn call_improved_backend_code(
+        &mut self,
+        context: &AgentContext,
+        factsheet: &mut FactSheet,
+        lint_errors: Option<String>,
+    ) -> Result<(), AutoGptError> {
+        let backend_code: String = factsheet
+            .backend_code
+            .as_ref()
+            .map(|backend_files| backend_files.to_manifest())
+            .unwrap_or_default();
+
+        let lint_errors_context: String = match lint_errors {
+            Some(lint_errors) => format!("LINT_ERRORS: {} \n", lint_errors),
+            None => String::new(),
+        };
+
+        let msg_context: String = format!(
+            "CODE TEMPLATE: {} \n PROJECT_DESCRIPTION: {} \n PROJECT_SCOPE: {:?} \n EXTERNAL_URLS: {:?} \n {}{}",
+            backend_code,
+            factsheet.project_description,
+            factsheet.project_scope,
+            factsheet.external_urls,
+            self.api_contract_context(),
+            lint_errors_context
         );
 
+        if context.config.edit_mode == EditMode::Patch
+            && self
+                .call_backend_edits(
+                    context,
+                    factsheet,
+                    msg_context.clone(),
+                    get_function_string!(print_improved_webserver_edits),
+                    print_improved_webserver_edits,
+                    &context.config.model,
+                )
+                .await?
+        {
+            return Ok(());
+        }
+
         let ai_response: String = ai_task_request(
+            context,
             msg_context,
             &self.attributes.position,
             get_function_string!(print_improved_webserver_code),
             print_improved_webserver_code,
         )
-        .await;
+        .await?;
 
-        save_backend_code(&ai_response);
+        self.last_ai_function = get_function_string!(print_improved_webserver_code);
 
-        factsheet.backend_code = Some(ai_response);
+        match ProjectFiles::from_manifest(&ai_response, MAIN_FILE) {
+            Ok(backend_files) => self.replace_backend_files(context, factsheet, backend_files)?,
+            Err(e) => {
+                let err_msg: String =
+                    format!("Keeping previous code, improved code unusable: {}", e);
+                context.emit(PrintCommand::Issue, &self.attributes.position, &err_msg);
+            }
+        }
+        Ok(())
     }
 
-    async fn call_fix_code_bugs(&mut self, factsheet: &mut FactSheet) {
-        let mut msg_context: String = format!(
-            "BROKEN_CODE: {:?} \n ERROR_BUGS: {:?} \n THIS FUNCTION ONLY OUTPUT CODE. JUST OUTPUT THE CODE.",
-            factsheet.backend_code, self.bug_errors
+    async fn call_fix_code_bugs(
+        &mut self,
+        context: &AgentContext,
+        factsheet: &mut FactSheet,
+    ) -> Result<(), AutoGptError> {
+        let mut backend_files: ProjectFiles = factsheet.backend_code.clone().unwrap_or_default();
+        let bug_errors: String = self.bug_errors.clone().unwrap_or_default();
+
+        let mut broken_paths: Vec<String> = backend_files.files_named_in(&bug_errors);
+        if broken_paths.is_empty() {
+            broken_paths = backend_files.paths();
+        }
+        let other_paths: Vec<String> = backend_files
+            .paths()
+            .into_iter()
+            .filter(|path| !broken_paths.contains(path))
+            .collect();
+
+        let fix_model: String = self.fix_model(context);
+        let fix_hints: Vec<&str> = context.config.fix_hints.for_errors(&bug_errors);
+        let fix_hints_context: String = match fix_hints.is_empty() {
+            true => String::new(),
+            false => format!("FIX_HINTS: - {} \n", fix_hints.join("\n - ")),
+        };
+
+        let msg_context: String = format!(
+            "BROKEN_CODE: {} \n OTHER_FILES: {:?} \n ERROR_BUGS: {} \n {}THIS FUNCTION ONLY OUTPUT CODE. JUST OUTPUT THE CODE.",
+            backend_files.subset_manifest(&broken_paths),
+            other_paths,
+            bug_errors,
+            fix_hints_context
         );
 
-        let ai_response: String = ai_task_request(
+        if context.config.edit_mode == EditMode::Patch
+            && self
+                .call_backend_edits(
+                    context,
+                    factsheet,
+                    msg_context.clone(),
+                    get_function_string!(print_fixed_code_edits),
+                    print_fixed_code_edits,
+                    &fix_model,
+                )
+                .await?
+        {
+            return Ok(());
+        }
+
+        let ai_response: String = ai_task_request_with_model(
+            context,
             msg_context,
             &self.attributes.position,
             get_function_string!(print_fixed_code),
             print_fixed_code,
+            &fix_model,
         )
-        .await;
+        .await?;
 
-        save_backend_code(&ai_response);
+        self.last_ai_function = get_function_string!(print_fixed_code);
 
-        factsheet.backend_code = Some(ai_response);
+        let fallback_path: &str = match broken_paths.as_slice() {
+            [only_path] => only_path,
+            _ => MAIN_FILE,
+        };
+
+        match ProjectFiles::from_manifest(&ai_response, fallback_path) {
+            Ok(fixed_files) => {
+                save_backend_files(&context.workspace, &fixed_files)?;
+                backend_files.merge(fixed_files);
+                factsheet.backend_code = Some(backend_files);
+            }
+            Err(e) => {
+                let err_msg: String = format!("Keeping previous code, fixed code unusable: {}", e);
+                context.emit(PrintCommand::Issue, &self.attributes.position, &err_msg);
+            }
+        }
+        Ok(())
     }
 
-    async fn call_extract_rest_api_endpoints(&self) -> String {
-        let backend_code = read_exec_main_contents();
+    // Reads the routes from the code itself and only asks the model when that fails.
+    async fn call_extract_rest_api_endpoints(
+        &mut self,
+        context: &AgentContext,
+    ) -> Result<Vec<RouteObject>, AutoGptError> {
+        let backend_files: ProjectFiles = read_backend_files(&context.workspace)?;
+
+        match extract_routes(&backend_files) {
+            Ok(api_endpoints) => return Ok(api_endpoints),
+            Err(e) => {
+                let err_msg: String = format!("Asking the model for API endpoints instead: {}", e);
+                context.emit(PrintCommand::Issue, &self.attributes.position, &err_msg);
+            }
+        }
 
-        let msg_context: String = format!("CODE_INPUT: {:?}", backend_code);
+        let msg_context: String = format!("CODE_INPUT: {}", backend_files.to_manifest());
 
-        let ai_response: String = ai_task_request(
+        let api_endpoints: Vec<RouteObject> = ai_task_request_decoded::<Vec<RouteObject>>(
+            context,
             msg_context,
             &self.attributes.position,
             get_function_string!(print_rest_api_endpoints),
             print_rest_api_endpoints,
         )
-        .await;
+        .await?;
 
-        ai_response
+        self.last_ai_function = get_function_string!(print_rest_api_endpoints);
+        Ok(api_endpoints)
     }
 }
+
+// Plans the files and writes a first version of them. Every call is an independent draft.
+async fn draft_backend_files(
+    context: AgentContext,
+    position: String,
+    plan_context: String,
+) -> Result<ProjectFiles, AutoGptError> {
+    let file_plan: Vec<PlannedFile> = ai_task_request_decoded::<Vec<PlannedFile>>(
+        &context,
+        plan_context.clone(),
+        &position,
+        get_function_string!(print_backend_file_plan),
+        print_backend_file_plan,
+    )
+    .await?;
+
+    let msg_context: String = format!(
+        "{} FILE_PLAN: {} \n",
+        plan_context,
+        serde_json::to_value(&file_plan).unwrap_or_default()
+    );
+
+    let ai_response: String = ai_task_request(
+        &context,
+        msg_context,
+        &position,
+        get_function_string!(print_backend_webserver_code),
+        print_backend_webserver_code,
+    )
+    .await?;
+
+    ProjectFiles::from_manifest(&ai_response, MAIN_FILE).map_err(|e| AutoGptError::Decode {
+        operation: get_function_string!(print_backend_webserver_code).to_string(),
+        reason: e.to_string(),
+    })
+}
+
+// Builds a candidate and contract-tests the routes it serves. Its bugs are only scored here; the
+// fix loop runs on the chosen candidate afterwards.
+async fn evaluate_candidate(
+    candidate: usize,
+    mut test_bench: TestBench,
+    backend_files: ProjectFiles,
+    api_contract: Option<Vec<RouteObject>>,
+    project_scope: Option<ProjectScope>,
+) -> (CandidateScore, ProjectFiles) {
+    let unusable =
+        |e: AutoGptError| CandidateScore::unusable(candidate, format!("evaluation failed: {}", e));
+
+    let build_check: BuildCheck = match test_bench.build().await {
+        Ok(build_check) => build_check,
+        Err(e) => return (unusable(e), backend_files),
+    };
+    let mut score: CandidateScore = CandidateScore {
+        candidate,
+        compiles: build_check.bug_report.is_none(),
+        ..CandidateScore::default()
+    };
+    if let Some(build_diagnostics) = &build_check.diagnostics {
+        score.errors = build_diagnostics.count(DiagnosticLevel::Error);
+        score.warnings = build_diagnostics.count(DiagnosticLevel::Warning);
+    }
+    if !score.compiles {
+        return (score, backend_files);
+    }
+
+    let routes: Vec<RouteObject> = extract_routes(&backend_files).unwrap_or_default();
+    (score.scope_matched, score.scope_checked) =
+        scope_coverage(&routes, project_scope.as_ref(), api_contract.as_deref());
+
+    let server_check: ServerCheck = match test_bench.test_server(&routes).await {
+        Ok(server_check) => server_check,
+        Err(e) => return (unusable(e), backend_files),
+    };
+    score.endpoints_tested = server_check.contract_report.results.len();
+    score.endpoints_passed = score.endpoints_tested - server_check.contract_report.failures().len();
+
+    (score, backend_files)
+}
+
 #[async_trait]
 impl SpecialFunctions for AgentBackendDeveloper {
     fn get_attributes_from_agent(&self) -> &BasicAgent {
         &self.attributes
     }
 
+    fn get_attributes_mut(&mut self) -> &mut BasicAgent {
+        &mut self.attributes
+    }
+
+    fn get_checkpointer(&self) -> Option<&Checkpointer> {
+        self.checkpointer.as_ref()
+    }
+
+    fn attach_checkpointer(&mut self, checkpointer: Checkpointer) {
+        self.checkpointer = Some(checkpointer);
+    }
+
+    fn progress(&self) -> AgentProgress {
+        AgentProgress {
+            position: self.attributes.position.clone(),
+            state: self.attributes.state,
+            bug_cont: self.bug_cont,
+            bug_errors: self.bug_errors.clone(),
+            api_contract: self.api_contract.clone(),
+            lint_errors: self.lint_errors.clone(),
+            lint_rounds: self.lint_rounds,
+            escalations: self.escalations,
+        }
+    }
+
+    fn restore_progress(&mut self, progress: &AgentProgress) {
+        self.attributes.state = progress.state;
+        self.bug_cont = progress.bug_cont;
+        self.bug_errors = progress.bug_errors.clone();
+        self.api_contract = progress.api_contract.clone();
+        self.lint_errors = progress.lint_errors.clone();
+        self.lint_rounds = progress.lint_rounds;
+        self.escalations = progress.escalations;
+    }
+
+    // Scope, urls and an API contract shape the server when present; without them it is built
+    // from the description alone.
+    fn reads(&self) -> Vec<FactSheetInput> {
+        vec![
+            FactSheetInput::Required(FactSheetField::ProjectDescription),
+            FactSheetInput::Optional(FactSheetField::ProjectScope),
+            FactSheetInput::Optional(FactSheetField::ExternalUrls),
+            FactSheetInput::Optional(FactSheetField::ApiEndpointSchema),
+        ]
+    }
+
+    fn writes(&self) -> Vec<FactSheetField> {
+        vec![
+            FactSheetField::BackendCode,
+            FactSheetField::ApiEndpointSchema,
+        ]
+    }
+
     async fn execute(
         &mut self,
+        context: &AgentContext,
         factsheet: &mut FactSheet,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        while self.attributes.state != AgentState::Finished {
+    ) -> Result<(), AutoGptError> {
+        let mut test_bench: TestBench = TestBench::new(
+            context.workspace.clone(),
+            &self.attributes.position,
+            context,
+        );
+
+        while !matches!(
+            self.attributes.state,
+            AgentState::Finished | AgentState::Failed
+        ) {
+            self.save_checkpoint(context, factsheet);
+            context.cancellation.check()?;
+
             match &self.attributes.state {
                 AgentState::Discovery => {
-                    self.call_initial_backend_code(factsheet).await;
+                    self.api_contract = factsheet.api_endpoint_schema.clone();
+                    if context.config.candidates > 1 {
+                        self.call_candidate_backend_code(context, factsheet).await?;
+                    } else {
+                        self.call_initial_backend_code(context, factsheet).await?;
+                    }
+                    self.commit_step(context, StepOutcome::NotBuilt);
                     self.attributes.state = AgentState::Working;
                     continue;
                 }
                 AgentState::Working => {
-                    if self.bug_cont == 0 {
-                        self.call_improved_backend_code(factsheet).await;
+                    if let Some(lint_errors) = self.lint_errors.take() {
+                        self.call_improved_backend_code(context, factsheet, Some(lint_errors))
+                            .await?;
+                    } else if self.bug_errors.is_none() {
+                        self.call_improved_backend_code(context, factsheet, None)
+                            .await?;
                     } else {
-                        self.call_fix_code_bugs(factsheet).await;
+                        self.call_fix_code_bugs(context, factsheet).await?;
                     }
                     self.attributes.state = AgentState::UnitTesting;
                     continue;
                 }
                 AgentState::UnitTesting => {
-                    PrintCommand::UnitTest.print_agent_message(
-                        &self.attributes.position.as_str(),
-                        "Backend Code unit Testing: Requesting user input",
-                    );
-
-                    let is_safe_code = confirm_safe_code();
+                    self.confirm_running_code(context)?;
 
-                    if !is_safe_code {
-                        panic!("Better go work on some AI aliment instead..")
+                    let build_check: BuildCheck = test_bench.build().await?;
+                    // A Ctrl-C kills the build, which is not a bug in the code.
+                    context.cancellation.check()?;
+                    if let Some(bug_report) = build_check.bug_report {
+                        self.record_bugs(context, bug_report, factsheet)?;
+                        continue;
                     }
 
-                    PrintCommand::UnitTest.print_agent_message(
-                        &self.attributes.position.as_str(),
-                        "Backend Code unit Testing: building project...",
-                    );
-
-                    let build_backend_server: std::process::Output = Command::new("cargo")
-                        .arg("build")
-                        .current_dir(WEB_SERVER_PROJECT_PATH)
-                        .stdout(Stdio::piped())
-                        .stdout(Stdio::piped())
-                        .output()
-                        .expect("Failed to run backend application");
-
-                    if build_backend_server.status.success() {
-                        self.bug_cont = 0;
-                        PrintCommand::UnitTest.print_agent_message(
-                            &self.attributes.position.as_str(),
-                            "Backend Code unit Testing: Test server build successful...",
-                        );
-                    } else {
-                        let error_arr: Vec<u8> = build_backend_server.stderr;
-                        let error_str: String = String::from_utf8(error_arr).unwrap();
-
-                        self.bug_cont += 1;
-                        self.bug_errors = Some(error_str);
-
-                        if self.bug_cont > 2 {
-                            PrintCommand::Issue.print_agent_message(
-                                &self.attributes.position.as_str(),
-                                "Backend Code Unit Testing: Too many bugs found in code.",
-                            );
-                            panic!("Error: Too many bugs");
-                        }
+                    let style_report: StyleReport =
+                        test_bench.style_check(&context.config.lint_policy).await?;
+                    let backend_files: ProjectFiles = read_backend_files(&context.workspace)?;
+                    let denied_lints: Vec<String> =
+                        errors_for_fix(&style_report.lints, &backend_files);
+                    if !style_report.reformatted_files.is_empty() {
+                        factsheet.backend_code = Some(backend_files);
+                    }
 
+                    // Denied lints go back to the improvement step a limited number of times;
+                    // after that they are only reported.
+                    if !denied_lints.is_empty()
+                        && self.lint_rounds < context.config.lint_policy.max_rounds
+                    {
+                        self.lint_rounds += 1;
+                        self.commit_step(context, StepOutcome::LintErrors);
+                        self.lint_errors = Some(denied_lints.join("\n"));
                         self.attributes.state = AgentState::Working;
                         continue;
                     }
+                    self.commit_step(context, StepOutcome::BuildOk);
 
-                    let api_endpoints_str: String = self.call_extract_rest_api_endpoints().await;
-
-                    let api_endpoints: Vec<RouteObject> =
-                        serde_json::from_str(api_endpoints_str.as_str())
-                            .expect("Failed to decode API Endpoints");
-
-                    let check_endpoint: Vec<RouteObject> = api_endpoints
-                        .iter()
-                        .filter(|&route_object| {
-                            route_object.method == "get" && route_object.is_route_dynamic == "false"
-                        })
-                        .cloned()
-                        .collect();
+                    let mut api_endpoints: Vec<RouteObject> =
+                        self.call_extract_rest_api_endpoints(context).await?;
 
-                    factsheet.api_endpoint_schema = Some(check_endpoint.clone());
-
-                    PrintCommand::UnitTest.print_agent_message(
-                        &self.attributes.position.as_str(),
-                        "Backend Code Unit Testing: String web server...",
-                    );
-
-                    let mut run_backend_server: std::process::Child = Command::new("cargo")
-                        .arg("run")
-                        .current_dir(WEB_SERVER_PROJECT_PATH)
-                        .stdout(Stdio::piped())
-                        .stdout(Stdio::piped())
-                        .spawn()
-                        .expect("Failed to run backend application");
-
-                    PrintCommand::UnitTest.print_agent_message(
-                        &self.attributes.position.as_str(),
-                        "Backend Code Unit Testing: Launching tests on server in 5 seconds...",
-                    );
+                    if let Some(api_contract) = self.api_contract.clone() {
+                        let route_diff: RouteDiff = diff_routes(&api_contract, &api_endpoints);
+                        if !route_diff.is_empty() {
+                            let bug_report: BugReport = BugReport::new(
+                                BugKind::ContractMismatch,
+                                route_diff.to_string().lines().map(String::from).collect(),
+                            );
+                            context.emit(
+                                PrintCommand::Issue,
+                                self.attributes.position.as_str(),
+                                &bug_report.to_string(),
+                            );
+                            self.record_bugs(context, bug_report, factsheet)?;
+                            continue;
+                        }
+                        api_endpoints = api_contract;
+                    }
 
-                    let seconds_sleep: Duration = Duration::from_secs(5);
-                    time::sleep(seconds_sleep);
+                    factsheet.api_endpoint_schema = Some(api_endpoints.clone());
 
-                    for endpoint in check_endpoint {
-                        let testing_msg: String =
-                            format!("Testing endpoint '{}'...", endpoint.route);
+                    let server_check: ServerCheck = test_bench.test_server(&api_endpoints).await?;
+                    context.cancellation.check()?;
 
-                        PrintCommand::UnitTest.print_agent_message(
-                            &self.attributes.position.as_str(),
-                            testing_msg.as_str(),
+                    if let Some(bug_report) = server_check.bug_report {
+                        context.emit(
+                            PrintCommand::Issue,
+                            self.attributes.position.as_str(),
+                            &bug_report.to_string(),
                         );
-
-                        let client: Client = Client::builder()
-                            .timeout(Duration::from_secs(5))
-                            .build()
-                            .unwrap();
-
-                        let url: String = format!("http://localhost:8080{}", endpoint.route);
-
-                        match check_status_code(&client, &url).await {
-                            Ok(status_code) => {
-                                if status_code != 200 {
-                                    let err_msg: String = format!(
-                                        "WARING: Failed to call backend url endpoint {}",
-                                        endpoint.route
-                                    );
-
-                                    PrintCommand::UnitTest.print_agent_message(
-                                        &self.attributes.position.as_str(),
-                                        err_msg.as_str(),
-                                    );
-                                }
-                            }
-                            Err(e) => {
-                                run_backend_server
-                                    .kill()
-                                    .expect("Failed to kill backend web server");
-
-                                let err_msg: String = format!("Error checking backend {}", e);
-
-                                PrintCommand::UnitTest.print_agent_message(
-                                    &self.attributes.position.as_str(),
-                                    err_msg.as_str(),
-                                );
-                            }
-                        }
+                        self.record_bugs(context, bug_report, factsheet)?;
+                        continue;
                     }
 
-                    save_api_endpoints(&api_endpoints_str);
+                    context.emit(
+                        PrintCommand::UnitTest,
+                        self.attributes.position.as_str(),
+                        "Backend Code Unit Testing: All contract tests passed...",
+                    );
+
+                    save_api_schema(
+                        &context.workspace,
+                        &factsheet.project_description,
+                        &api_endpoints,
+                    )?;
+                    self.commit_step(context, StepOutcome::BuildOk);
 
-                    PrintCommand::UnitTest.print_agent_message(
-                        &self.attributes.position.as_str(),
+                    context.emit(
+                        PrintCommand::UnitTest,
+                        self.attributes.position.as_str(),
                         "Backend testing complete...",
                     );
 
-                    run_backend_server
-                        .kill()
-                        .expect("Failed to kill backend web server on completion");
-
+                    self.bug_cont = 0;
+                    self.bug_errors = None;
                     self.attributes.state = AgentState::Finished;
                 }
                 _ => {}
             }
         }
+
+        self.save_checkpoint(context, factsheet);
+
+        // The code, diagnostics and history stay in the workspace for the run report.
+        if self.attributes.state == AgentState::Failed {
+            return Err(AutoGptError::AgentFailed {
+                position: self.attributes.position.clone(),
+                reason: format!(
+                    "gave up after using {} escalations, last errors:\n{}",
+                    self.escalations,
+                    self.bug_errors.clone().unwrap_or_default()
+                ),
+            });
+        }
         Ok(())
     }
 }
@@ -304,9 +939,22 @@ impl SpecialFunctions for AgentBackendDeveloper {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        helpers::{project_history::ProjectHistory, workspace::new_run_id},
+        models::agents::agent_context::CancellationToken,
+    };
+    use std::{env, fs, path::PathBuf};
 
     #[tokio::test]
     async fn tests_writing_backend_developer() {
+        let base_dir: PathBuf = env::temp_dir().join(format!("backend-{}", new_run_id()));
+        let template_dir: PathBuf =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("web_gpt_template");
+        let workspace: Workspace = Workspace::create_in(&base_dir, &template_dir, "run")
+            .expect("Failed to create workspace");
+        ProjectHistory::init(workspace.root()).expect("Failed to start the project history");
+        let context: AgentContext = AgentContext::from_env(workspace, CancellationToken::default())
+            .expect("Failed to configure the agent context");
         let mut agent: AgentBackendDeveloper = AgentBackendDeveloper::new();
 
         let factsheet_str: &str = r#"
@@ -328,8 +976,9 @@ mod tests {
 
         agent.attributes.state = AgentState::Discovery;
         agent
-            .execute(&mut factsheet)
+            .execute(&context, &mut factsheet)
             .await
             .expect("Failed to execute Backend Developer agent");
+        let _ = fs::remove_dir_all(&base_dir);
     }
 }
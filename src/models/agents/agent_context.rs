@@ -0,0 +1,231 @@
+use crate::{
+    apis::{
+        call_request::DEFAULT_MODEL,
+        llm_provider::{LlmProvider, OpenAiProvider},
+    },
+    helpers::{
+        code_edits::EditMode,
+        command_line::{confirm_safe_code, PrintCommand},
+        process_supervisor::ProcessTimeouts,
+        test_bench::{LintPolicy, SandboxLimits, SandboxMode},
+        workspace::Workspace,
+    },
+    models::{
+        agent_manager::pipeline::AgentSpec,
+        general::{
+            candidate_score::{candidates_from_env, DEFAULT_CANDIDATES},
+            error::AutoGptError,
+            fix_hints::FixHints,
+            fix_policy::FixPolicy,
+        },
+    },
+};
+use reqwest::Client;
+use std::{
+    env,
+    fmt::Debug,
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+const CONFIG_POSITION: &str = "Configuration";
+const DEFAULT_LLM_RETRIES: u8 = 1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentEvent {
+    pub kind: PrintCommand,
+    pub position: String,
+    pub message: String,
+}
+
+// Where agents report progress and problems.
+pub trait EventSink: Debug + Send + Sync {
+    fn emit(&self, event: AgentEvent);
+}
+
+#[derive(Debug)]
+pub struct ConsoleEvents;
+
+impl EventSink for ConsoleEvents {
+    fn emit(&self, event: AgentEvent) {
+        event
+            .kind
+            .print_agent_message(&event.position, &event.message);
+    }
+}
+
+// The questions agents put to the person running them.
+pub trait UserInteraction: Debug + Send + Sync {
+    fn confirm_safe_code(&self) -> io::Result<bool>;
+}
+
+#[derive(Debug)]
+pub struct TerminalUser;
+
+impl UserInteraction for TerminalUser {
+    fn confirm_safe_code(&self) -> io::Result<bool> {
+        confirm_safe_code()
+    }
+}
+
+// Set once to stop every agent sharing it after its current step.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    pub fn check(&self) -> Result<(), AutoGptError> {
+        match self.is_cancelled() {
+            true => Err(AutoGptError::Cancelled),
+            false => Ok(()),
+        }
+    }
+}
+
+// The settings agents run with. `model` comes from OPEN_AI_MODEL; `objective` and
+// `llm_retries` only from the pipeline; the rest from the variables their types read.
+#[derive(Debug, Clone)]
+pub struct AgentConfig {
+    pub model: String,
+    pub objective: Option<String>,
+    pub llm_retries: u8,
+    pub edit_mode: EditMode,
+    pub candidates: usize,
+    pub lint_policy: LintPolicy,
+    pub fix_policy: FixPolicy,
+    pub fix_hints: FixHints,
+    pub timeouts: ProcessTimeouts,
+    pub sandbox_mode: SandboxMode,
+    // Only enforced by the Linux sandbox.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub sandbox_limits: SandboxLimits,
+}
+
+// The built-in settings, whatever the environment says.
+impl Default for AgentConfig {
+    fn default() -> Self {
+        Self {
+            model: DEFAULT_MODEL.to_string(),
+            objective: None,
+            llm_retries: DEFAULT_LLM_RETRIES,
+            edit_mode: EditMode::default(),
+            candidates: DEFAULT_CANDIDATES,
+            lint_policy: LintPolicy::default(),
+            fix_policy: FixPolicy::default(),
+            fix_hints: FixHints::built_in(),
+            timeouts: ProcessTimeouts::default(),
+            sandbox_mode: SandboxMode::default(),
+            sandbox_limits: SandboxLimits::default(),
+        }
+    }
+}
+
+impl AgentConfig {
+    // Invalid fix hints or policies are reported and replaced with the defaults.
+    pub fn from_env(events: &dyn EventSink) -> Self {
+        let warn = |message: String| {
+            events.emit(AgentEvent {
+                kind: PrintCommand::Issue,
+                position: CONFIG_POSITION.to_string(),
+                message,
+            })
+        };
+
+        let fix_hints: FixHints = FixHints::from_env().unwrap_or_else(|e| {
+            warn(format!("Using the built-in fix hints only: {}", e));
+            FixHints::built_in()
+        });
+        let fix_policy: FixPolicy = FixPolicy::from_env().unwrap_or_else(|e| {
+            warn(format!("Using the default fix policy: {}", e));
+            FixPolicy::default()
+        });
+
+        Self {
+            model: env::var("OPEN_AI_MODEL").unwrap_or_else(|_| DEFAULT_MODEL.to_string()),
+            objective: None,
+            llm_retries: DEFAULT_LLM_RETRIES,
+            edit_mode: EditMode::from_env(),
+            candidates: candidates_from_env(),
+            lint_policy: LintPolicy::from_env(),
+            fix_policy,
+            fix_hints,
+            timeouts: ProcessTimeouts::from_env(),
+            sandbox_mode: SandboxMode::from_env(),
+            sandbox_limits: SandboxLimits::from_env(),
+        }
+    }
+}
+
+// Everything an agent reaches outside its own state. Cloning shares the provider, client, sinks
+// and cancellation token.
+#[derive(Debug, Clone)]
+pub struct AgentContext {
+    pub provider: Arc<dyn LlmProvider>,
+    pub http_client: Client,
+    pub config: AgentConfig,
+    pub workspace: Workspace,
+    pub events: Arc<dyn EventSink>,
+    pub user: Arc<dyn UserInteraction>,
+    pub cancellation: CancellationToken,
+}
+
+impl AgentContext {
+    // Talks to OpenAI and the terminal, configured from the environment.
+    pub fn from_env(
+        workspace: Workspace,
+        cancellation: CancellationToken,
+    ) -> Result<Self, AutoGptError> {
+        let http_client: Client = Client::builder()
+            .build()
+            .map_err(|e| AutoGptError::Config(format!("could not build an HTTP client: {}", e)))?;
+        let events: Arc<dyn EventSink> = Arc::new(ConsoleEvents);
+
+        Ok(Self {
+            provider: Arc::new(OpenAiProvider::new(http_client.clone())),
+            config: AgentConfig::from_env(events.as_ref()),
+            http_client,
+            workspace,
+            events,
+            user: Arc::new(TerminalUser),
+            cancellation,
+        })
+    }
+
+    // The context for one agent of the pipeline, with the options its spec sets.
+    pub fn for_agent(&self, spec: &AgentSpec) -> Self {
+        let mut context: AgentContext = self.clone();
+        if let Some(model) = &spec.model {
+            context.config.model = model.clone();
+        }
+        if let Some(objective) = &spec.objective {
+            context.config.objective = Some(objective.clone());
+        }
+        if let Some(llm_retries) = spec.llm_retries {
+            context.config.llm_retries = llm_retries;
+        }
+        if let Some(max_fix_iterations) = spec.max_fix_iterations {
+            context.config.fix_policy.max_iterations = max_fix_iterations;
+        }
+        context
+    }
+
+    pub fn emit(&self, kind: PrintCommand, position: &str, message: &str) {
+        self.events.emit(AgentEvent {
+            kind,
+            position: position.to_string(),
+            message: message.to_string(),
+        });
+    }
+}
@@ -1,17 +1,16 @@
-use crate::models::agent_basic::basic_agent::BasicAgent;
+use crate::{
+    helpers::command_line::PrintCommand,
+    models::{
+        agent_basic::basic_agent::BasicAgent,
+        agent_manager::checkpoint::{AgentProgress, Checkpointer},
+        agents::agent_context::AgentContext,
+        general::{api_schema::RouteObject, error::AutoGptError, project_files::ProjectFiles},
+    },
+};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
-pub struct RouteObject {
-    pub is_route_dynamic: String,
-    pub method: String,
-    pub request_body: serde_json::Value,
-    pub response: serde_json::Value,
-    pub route: String,
-}
-
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub struct ProjectScope {
     pub is_crud_required: bool,
@@ -19,17 +18,122 @@ pub struct ProjectScope {
     pub is_external_urls_required: bool,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PlannedFile {
+    pub path: String,
+    pub purpose: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct FactSheet {
     pub project_description: String,
     pub project_scope: Option<ProjectScope>,
     pub external_urls: Option<Vec<String>>,
-    pub backend_code: Option<String>,
+    pub backend_code: Option<ProjectFiles>,
     pub api_endpoint_schema: Option<Vec<RouteObject>>,
 }
 
+#[cfg(test)]
+impl FactSheet {
+    // A factsheet as a run starts it, with nothing but the project description.
+    pub fn described(project_description: &str) -> Self {
+        Self {
+            project_description: project_description.to_string(),
+            project_scope: None,
+            external_urls: None,
+            backend_code: None,
+            api_endpoint_schema: None,
+        }
+    }
+}
+
+// The factsheet fields agents declare they read and write, named as they are serialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FactSheetField {
+    ProjectDescription,
+    ProjectScope,
+    ExternalUrls,
+    BackendCode,
+    ApiEndpointSchema,
+}
+
+impl FactSheetField {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ProjectDescription => "project_description",
+            Self::ProjectScope => "project_scope",
+            Self::ExternalUrls => "external_urls",
+            Self::BackendCode => "backend_code",
+            Self::ApiEndpointSchema => "api_endpoint_schema",
+        }
+    }
+
+    pub fn is_set(&self, factsheet: &FactSheet) -> bool {
+        match self {
+            Self::ProjectDescription => !factsheet.project_description.is_empty(),
+            Self::ProjectScope => factsheet.project_scope.is_some(),
+            Self::ExternalUrls => factsheet.external_urls.is_some(),
+            Self::BackendCode => factsheet.backend_code.is_some(),
+            Self::ApiEndpointSchema => factsheet.api_endpoint_schema.is_some(),
+        }
+    }
+
+    pub fn copy(&self, from: &FactSheet, to: &mut FactSheet) {
+        match self {
+            Self::ProjectDescription => to.project_description = from.project_description.clone(),
+            Self::ProjectScope => to.project_scope = from.project_scope,
+            Self::ExternalUrls => to.external_urls = from.external_urls.clone(),
+            Self::BackendCode => to.backend_code = from.backend_code.clone(),
+            Self::ApiEndpointSchema => to.api_endpoint_schema = from.api_endpoint_schema.clone(),
+        }
+    }
+}
+
+// An agent is not started until every required input is set. Agents writing any of its inputs,
+// optional or not, run before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FactSheetInput {
+    Required(FactSheetField),
+    Optional(FactSheetField),
+}
+
+impl FactSheetInput {
+    pub fn field(&self) -> FactSheetField {
+        match self {
+            Self::Required(field) | Self::Optional(field) => *field,
+        }
+    }
+}
+
 #[async_trait]
-pub trait SpecialFunctions: Debug {
+pub trait SpecialFunctions: Debug + Send + Sync {
     fn get_attributes_from_agent(&self) -> &BasicAgent;
-    async fn execute(&mut self, factsheet: &mut FactSheet) -> Result<(), Box<dyn std::error::Error>>;
+    fn get_attributes_mut(&mut self) -> &mut BasicAgent;
+    fn get_checkpointer(&self) -> Option<&Checkpointer>;
+    fn attach_checkpointer(&mut self, checkpointer: Checkpointer);
+    fn progress(&self) -> AgentProgress;
+    fn restore_progress(&mut self, progress: &AgentProgress);
+    fn reads(&self) -> Vec<FactSheetInput>;
+    fn writes(&self) -> Vec<FactSheetField>;
+
+    // Only the fields this agent writes are saved, so agents running side by side do not undo
+    // each other's changes.
+    fn save_checkpoint(&self, context: &AgentContext, factsheet: &FactSheet) {
+        if let Some(checkpointer) = self.get_checkpointer() {
+            if let Err(e) = checkpointer.save_progress(self.progress(), factsheet, &self.writes()) {
+                let err_msg: String = format!("Failed to save checkpoint: {}", e);
+                context.emit(
+                    PrintCommand::Issue,
+                    &self.get_attributes_from_agent().position,
+                    &err_msg,
+                );
+            }
+        }
+    }
+
+    async fn execute(
+        &mut self,
+        context: &AgentContext,
+        factsheet: &mut FactSheet,
+    ) -> Result<(), AutoGptError>;
 }
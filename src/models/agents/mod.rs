@@ -1,3 +1,4 @@
 pub mod agent_architect;
 pub mod agent_backend;
-pub mod agent_traits;
\ No newline at end of file
+pub mod agent_context;
+pub mod agent_traits;
@@ -0,0 +1,527 @@
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::{collections::BTreeMap, fmt, ops::Range, str::FromStr};
+
+const OPTIONAL_KEY: &str = "$optional";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum HttpMethod {
+    Get,
+    Post,
+    Put,
+    Patch,
+    Delete,
+    Head,
+    Options,
+}
+
+impl HttpMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Get => "get",
+            Self::Post => "post",
+            Self::Put => "put",
+            Self::Patch => "patch",
+            Self::Delete => "delete",
+            Self::Head => "head",
+            Self::Options => "options",
+        }
+    }
+}
+
+impl fmt::Display for HttpMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str().to_uppercase())
+    }
+}
+
+impl FromStr for HttpMethod {
+    type Err = String;
+
+    fn from_str(method: &str) -> Result<Self, Self::Err> {
+        match method.trim().to_lowercase().as_str() {
+            "get" => Ok(Self::Get),
+            "post" => Ok(Self::Post),
+            "put" => Ok(Self::Put),
+            "patch" => Ok(Self::Patch),
+            "delete" => Ok(Self::Delete),
+            "head" => Ok(Self::Head),
+            "options" => Ok(Self::Options),
+            _ => Err(format!("unknown HTTP method '{}'", method)),
+        }
+    }
+}
+
+impl Serialize for HttpMethod {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for HttpMethod {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let method: String = String::deserialize(deserializer)?;
+        method.parse().map_err(de::Error::custom)
+    }
+}
+
+// The field-type language used for request and response bodies. Scalars are written as
+// "string", "number", "bool" or "any", optional scalars as "string?", arrays as `[type]`,
+// objects as `{"field": type}` and optional arrays or objects as `{"$optional": type}`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldType {
+    String,
+    Number,
+    Bool,
+    Any,
+    Array(Box<FieldType>),
+    Object(BTreeMap<String, FieldType>),
+    Optional(Box<FieldType>),
+}
+
+impl FieldType {
+    // Accepts the loose shapes models print: type names in any case, Rust type names,
+    // example values, and "None"/"not_provided" sentinels.
+    pub fn from_lenient_value(value: &Value) -> Self {
+        match value {
+            Value::Null => Self::Any,
+            Value::Bool(_) => Self::Bool,
+            Value::Number(_) => Self::Number,
+            Value::String(name) => Self::from_type_name(name),
+            Value::Array(items) => Self::Array(Box::new(
+                items
+                    .first()
+                    .map(Self::from_lenient_value)
+                    .unwrap_or(Self::Any),
+            )),
+            Value::Object(fields) => match fields.get(OPTIONAL_KEY) {
+                Some(inner) if fields.len() == 1 => {
+                    Self::Optional(Box::new(Self::from_lenient_value(inner)))
+                }
+                _ => Self::Object(
+                    fields
+                        .iter()
+                        .map(|(name, field)| (name.clone(), Self::from_lenient_value(field)))
+                        .collect(),
+                ),
+            },
+        }
+    }
+
+    pub fn from_type_name(name: &str) -> Self {
+        let name: String = name.trim().to_lowercase();
+
+        if let Some(inner) = name.strip_suffix('?') {
+            return Self::Optional(Box::new(Self::from_type_name(inner)));
+        }
+        for (prefix, is_optional) in [("option<", true), ("optional<", true), ("vec<", false)] {
+            if let Some(inner) = name
+                .strip_prefix(prefix)
+                .and_then(|rest| rest.strip_suffix('>'))
+            {
+                let inner: Box<FieldType> = Box::new(Self::from_type_name(inner));
+                return if is_optional {
+                    Self::Optional(inner)
+                } else {
+                    Self::Array(inner)
+                };
+            }
+        }
+
+        match name.as_str() {
+            "string" | "str" | "&str" | "text" | "date" | "datetime" | "uuid" | "email" => {
+                Self::String
+            }
+            "number" | "integer" | "int" | "float" | "double" | "u8" | "u16" | "u32" | "u64"
+            | "u128" | "usize" | "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "f32"
+            | "f64" => Self::Number,
+            "bool" | "boolean" => Self::Bool,
+            "array" | "list" | "vec" => Self::Array(Box::new(Self::Any)),
+            "object" | "map" | "hashmap" => Self::Object(BTreeMap::new()),
+            _ => Self::Any,
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        match self {
+            Self::String => Value::from("string"),
+            Self::Number => Value::from("number"),
+            Self::Bool => Value::from("bool"),
+            Self::Any => Value::from("any"),
+            Self::Array(item) => Value::Array(vec![item.to_value()]),
+            Self::Object(fields) => Value::Object(
+                fields
+                    .iter()
+                    .map(|(name, field)| (name.clone(), field.to_value()))
+                    .collect::<Map<String, Value>>(),
+            ),
+            Self::Optional(inner) => match inner.to_value() {
+                Value::String(name) => Value::from(format!("{}?", name)),
+                inner_value => {
+                    let mut optional: Map<String, Value> = Map::new();
+                    optional.insert(OPTIONAL_KEY.to_string(), inner_value);
+                    Value::Object(optional)
+                }
+            },
+        }
+    }
+}
+
+impl Serialize for FieldType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_value().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for FieldType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self::from_lenient_value(&Value::deserialize(deserializer)?))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(try_from = "LenientRouteObject")]
+pub struct RouteObject {
+    pub is_route_dynamic: bool,
+    pub method: HttpMethod,
+    pub request_body: Option<FieldType>,
+    pub response: Option<FieldType>,
+    pub route: String,
+    pub path_params: Vec<String>,
+}
+
+impl RouteObject {
+    pub fn new(
+        method: HttpMethod,
+        route: &str,
+        request_body: Option<FieldType>,
+        response: Option<FieldType>,
+    ) -> Self {
+        let path_params: Vec<String> = parse_path_params(route);
+        Self {
+            is_route_dynamic: !path_params.is_empty(),
+            method,
+            request_body,
+            response,
+            route: route.to_string(),
+            path_params,
+        }
+    }
+}
+
+// Accepts both the typed format above and what `print_rest_api_endpoints` prints, where
+// booleans are "true"/"false" strings and missing bodies are "None" or "not_provided".
+#[derive(Deserialize)]
+struct LenientRouteObject {
+    route: String,
+    method: HttpMethod,
+    #[serde(default)]
+    is_route_dynamic: Option<Value>,
+    #[serde(default)]
+    request_body: Value,
+    #[serde(default)]
+    response: Value,
+}
+
+impl TryFrom<LenientRouteObject> for RouteObject {
+    type Error = String;
+
+    fn try_from(raw: LenientRouteObject) -> Result<Self, Self::Error> {
+        let mut route_object: RouteObject = RouteObject::new(
+            raw.method,
+            &raw.route,
+            lenient_body(&raw.request_body),
+            lenient_body(&raw.response),
+        );
+
+        route_object.is_route_dynamic = match raw.is_route_dynamic {
+            None | Some(Value::Null) => route_object.is_route_dynamic,
+            Some(Value::Bool(is_dynamic)) => is_dynamic,
+            Some(Value::String(is_dynamic)) => match is_dynamic.trim().to_lowercase().as_str() {
+                "true" => true,
+                "false" => false,
+                _ => return Err(format!("invalid is_route_dynamic '{}'", is_dynamic)),
+            },
+            Some(other) => return Err(format!("invalid is_route_dynamic {}", other)),
+        };
+
+        Ok(route_object)
+    }
+}
+
+fn lenient_body(body: &Value) -> Option<FieldType> {
+    match body {
+        Value::Null => None,
+        Value::String(sentinel) if sentinel.trim().eq_ignore_ascii_case("none") => None,
+        Value::String(sentinel) if sentinel.trim().eq_ignore_ascii_case("not_provided") => {
+            Some(FieldType::Any)
+        }
+        _ => Some(FieldType::from_lenient_value(body)),
+    }
+}
+
+// Routes are compared by method and path shape, so "/item/{id}" matches "/item/{item_id}".
+#[derive(Debug, Default, PartialEq)]
+pub struct RouteDiff {
+    pub missing: Vec<RouteObject>,
+    pub unexpected: Vec<RouteObject>,
+}
+
+impl RouteDiff {
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.unexpected.is_empty()
+    }
+}
+
+impl fmt::Display for RouteDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for route in &self.missing {
+            writeln!(f, "missing route: {} {}", route.method, route.route)?;
+        }
+        for route in &self.unexpected {
+            writeln!(f, "route not in contract: {} {}", route.method, route.route)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn diff_routes(expected: &[RouteObject], actual: &[RouteObject]) -> RouteDiff {
+    let is_listed_in = |route: &RouteObject, routes: &[RouteObject]| {
+        routes.iter().any(|other| {
+            other.method == route.method && route_shape(&other.route) == route_shape(&route.route)
+        })
+    };
+
+    RouteDiff {
+        missing: expected
+            .iter()
+            .filter(|route| !is_listed_in(route, actual))
+            .cloned()
+            .collect(),
+        unexpected: actual
+            .iter()
+            .filter(|route| !is_listed_in(route, expected))
+            .cloned()
+            .collect(),
+    }
+}
+
+// "/item/{id}/" -> "/item/{}"
+fn route_shape(route: &str) -> String {
+    let shape: String = route
+        .trim_end_matches('/')
+        .split('/')
+        .map(|segment| {
+            if segment.starts_with('{') && segment.ends_with('}') {
+                "{}"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<&str>>()
+        .join("/");
+    if shape.is_empty() {
+        "/".to_string()
+    } else {
+        shape
+    }
+}
+
+// "/item/{id}/tag/{tail:.*}" -> ["id", "tail"]
+pub fn parse_path_params(route: &str) -> Vec<String> {
+    template_params(route)
+        .into_iter()
+        .map(|(_, name)| name.to_string())
+        .collect()
+}
+
+// "/item/{id:[0-9]+}" -> "/item/{id}", the only form OpenAPI path templates allow.
+pub fn openapi_path(route: &str) -> String {
+    let mut path: String = String::new();
+    let mut copied: usize = 0;
+    for (span, name) in template_params(route) {
+        path.push_str(&route[copied..span.start]);
+        path.push_str(&format!("{{{}}}", name));
+        copied = span.end;
+    }
+    path.push_str(&route[copied..]);
+    path
+}
+
+// Every named `{...}` segment of a route template with its byte range.
+pub(crate) fn template_params(route: &str) -> Vec<(Range<usize>, &str)> {
+    let mut params: Vec<(Range<usize>, &str)> = vec![];
+    let mut offset: usize = 0;
+
+    while let Some(start) = route[offset..].find('{').map(|start| offset + start) {
+        let Some(end) = route[start..].find('}').map(|end| start + end + 1) else {
+            break;
+        };
+        let segment: &str = &route[start + 1..end - 1];
+        let name: &str = segment.split(':').next().unwrap_or_default().trim();
+        if !name.is_empty() {
+            params.push((start..end, name));
+        }
+        offset = end;
+    }
+
+    params
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn tests_route_objects_accept_llm_output_format() {
+        let llm_output: &str = r#"[
+          {
+            "route": "/item/{id}",
+            "is_route_dynamic": "true",
+            "method": "get",
+            "request_body": "None",
+            "response": {"id": "number", "name": "string", "completed": "bool"}
+          },
+          {
+            "route": "/item",
+            "is_route_dynamic": "false",
+            "method": "POST",
+            "request_body": {"id": "number", "tags": ["string"], "note": "Option<String>"},
+            "response": "None"
+          },
+          {
+            "route": "/crypto",
+            "is_route_dynamic": "false",
+            "method": "get",
+            "request_body": "None",
+            "response": "not_provided"
+          }
+        ]"#;
+
+        let routes: Vec<RouteObject> = serde_json::from_str(llm_output).unwrap();
+
+        assert!(routes[0].is_route_dynamic);
+        assert_eq!(routes[0].method, HttpMethod::Get);
+        assert_eq!(routes[0].path_params, vec!["id".to_string()]);
+        assert_eq!(routes[0].request_body, None);
+        assert_eq!(
+            routes[0].response,
+            Some(FieldType::Object(BTreeMap::from([
+                ("completed".to_string(), FieldType::Bool),
+                ("id".to_string(), FieldType::Number),
+                ("name".to_string(), FieldType::String),
+            ])))
+        );
+
+        assert!(!routes[1].is_route_dynamic);
+        assert_eq!(routes[1].method, HttpMethod::Post);
+        assert_eq!(
+            routes[1].request_body,
+            Some(FieldType::Object(BTreeMap::from([
+                ("id".to_string(), FieldType::Number),
+                (
+                    "note".to_string(),
+                    FieldType::Optional(Box::new(FieldType::String))
+                ),
+                (
+                    "tags".to_string(),
+                    FieldType::Array(Box::new(FieldType::String))
+                ),
+            ])))
+        );
+        assert_eq!(routes[1].response, None);
+
+        assert_eq!(routes[2].response, Some(FieldType::Any));
+    }
+
+    #[test]
+    fn tests_route_objects_round_trip_in_typed_format() {
+        let route: RouteObject = RouteObject::new(
+            HttpMethod::Put,
+            "/users/{user_id}/items/{id}",
+            Some(FieldType::Object(BTreeMap::from([
+                (
+                    "name".to_string(),
+                    FieldType::Optional(Box::new(FieldType::String)),
+                ),
+                (
+                    "labels".to_string(),
+                    FieldType::Optional(Box::new(FieldType::Array(Box::new(FieldType::String)))),
+                ),
+            ]))),
+            None,
+        );
+
+        let serialized: Value = serde_json::to_value(&route).unwrap();
+        assert_eq!(
+            serialized,
+            json!({
+                "is_route_dynamic": true,
+                "method": "put",
+                "request_body": {"labels": {"$optional": ["string"]}, "name": "string?"},
+                "response": null,
+                "route": "/users/{user_id}/items/{id}",
+                "path_params": ["user_id", "id"]
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<RouteObject>(serialized).unwrap(),
+            route
+        );
+    }
+
+    #[test]
+    fn tests_invalid_routes_are_rejected() {
+        let bad_method = serde_json::from_value::<RouteObject>(json!({
+            "route": "/item", "method": "fetch", "is_route_dynamic": "false"
+        }));
+        assert!(bad_method.is_err());
+
+        let bad_flag = serde_json::from_value::<RouteObject>(json!({
+            "route": "/item", "method": "get", "is_route_dynamic": "maybe"
+        }));
+        assert!(bad_flag.is_err());
+    }
+
+    #[test]
+    fn tests_path_params_are_parsed_from_route_template() {
+        assert_eq!(
+            parse_path_params("/item/{id}/tag/{tail:.*}"),
+            vec!["id".to_string(), "tail".to_string()]
+        );
+        assert!(parse_path_params("/items").is_empty());
+        assert!(parse_path_params("/broken/{id").is_empty());
+
+        assert_eq!(
+            openapi_path("/item/{id:[0-9]+}/tag/{ tail:.* }"),
+            "/item/{id}/tag/{tail}"
+        );
+        assert_eq!(openapi_path("/items/{id}"), "/items/{id}");
+        assert_eq!(openapi_path("/broken/{id"), "/broken/{id");
+    }
+
+    #[test]
+    fn tests_routes_are_compared_by_method_and_shape() {
+        let contract: Vec<RouteObject> = vec![
+            RouteObject::new(HttpMethod::Get, "/items", None, None),
+            RouteObject::new(HttpMethod::Get, "/items/{id}", None, None),
+            RouteObject::new(HttpMethod::Delete, "/items/{id}", None, None),
+        ];
+        let implemented: Vec<RouteObject> = vec![
+            RouteObject::new(HttpMethod::Get, "/items/", None, None),
+            RouteObject::new(HttpMethod::Get, "/items/{item_id}", None, None),
+            RouteObject::new(HttpMethod::Post, "/items", None, None),
+        ];
+
+        assert!(diff_routes(&contract, &contract).is_empty());
+
+        let diff: RouteDiff = diff_routes(&contract, &implemented);
+        assert_eq!(diff.missing, vec![contract[2].clone()]);
+        assert_eq!(diff.unexpected, vec![implemented[2].clone()]);
+        assert_eq!(
+            diff.to_string(),
+            "missing route: DELETE /items/{id}\nroute not in contract: POST /items\n"
+        );
+    }
+}
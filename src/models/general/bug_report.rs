@@ -0,0 +1,115 @@
+use std::fmt;
+
+const MAX_STDERR_CHARS: usize = 4000;
+const CARGO_RUNNING_MARKER: &str = "Running `";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BugKind {
+    CompileErrors,
+    ContractMismatch,
+    ServerCrashed,
+    ServerNotReady,
+    EndpointFailures,
+}
+
+impl fmt::Display for BugKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind: &str = match self {
+            Self::CompileErrors => "The project does not compile",
+            Self::ContractMismatch => "The server does not implement the API contract exactly",
+            Self::ServerCrashed => "The server exited while it was being tested",
+            Self::ServerNotReady => "The server never started accepting connections",
+            Self::EndpointFailures => "Some endpoints failed their checks",
+        };
+        write!(f, "{}", kind)
+    }
+}
+
+// What went wrong in a unit testing step, rendered as the ERROR_BUGS of the next fix step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BugReport {
+    pub kind: BugKind,
+    pub details: Vec<String>,
+    pub server_stderr: Option<String>,
+}
+
+impl BugReport {
+    pub fn new(kind: BugKind, details: Vec<String>) -> Self {
+        Self {
+            kind,
+            details,
+            server_stderr: None,
+        }
+    }
+
+    // Keeps only what the server printed after cargo started it, and at most the last few
+    // thousand characters of that.
+    pub fn with_server_stderr(mut self, stderr: &str) -> Self {
+        let server_output: &str = match stderr.find(CARGO_RUNNING_MARKER) {
+            Some(start) => stderr[start..]
+                .split_once('\n')
+                .map(|(_, rest)| rest)
+                .unwrap_or_default(),
+            None => stderr,
+        };
+        let server_output: &str = server_output.trim();
+
+        if !server_output.is_empty() {
+            let skip: usize = server_output
+                .chars()
+                .count()
+                .saturating_sub(MAX_STDERR_CHARS);
+            self.server_stderr = Some(server_output.chars().skip(skip).collect());
+        }
+        self
+    }
+}
+
+impl fmt::Display for BugReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}:", self.kind)?;
+        for detail in &self.details {
+            writeln!(f, "{}", detail)?;
+        }
+        if let Some(stderr) = &self.server_stderr {
+            writeln!(f, "SERVER STDERR:\n{}", stderr)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tests_bug_report_keeps_server_output_only() {
+        let stderr: &str = "   Compiling web_gpt_server v0.1.0\n    Finished dev\n     \
+            Running `target/debug/web_gpt_server`\n\
+            thread 'actix-rt|system:0|arbiter:0' panicked at src/handlers.rs:12:5:\n\
+            called `Option::unwrap()` on a `None` value\n";
+
+        let report: BugReport = BugReport::new(
+            BugKind::EndpointFailures,
+            vec!["GET /task/{id}: unexpected status 500".to_string()],
+        )
+        .with_server_stderr(stderr);
+
+        assert_eq!(
+            report.to_string(),
+            "Some endpoints failed their checks:\n\
+            GET /task/{id}: unexpected status 500\n\
+            SERVER STDERR:\n\
+            thread 'actix-rt|system:0|arbiter:0' panicked at src/handlers.rs:12:5:\n\
+            called `Option::unwrap()` on a `None` value\n"
+        );
+
+        let quiet: BugReport = BugReport::new(BugKind::ServerCrashed, vec![])
+            .with_server_stderr("     Running `target/debug/web_gpt_server`\n");
+        assert_eq!(quiet.server_stderr, None);
+
+        let long: BugReport =
+            BugReport::new(BugKind::ServerCrashed, vec![]).with_server_stderr(&"x".repeat(5000));
+        assert_eq!(long.server_stderr.unwrap().len(), MAX_STDERR_CHARS);
+    }
+}
@@ -0,0 +1,189 @@
+use crate::models::{
+    agents::agent_traits::ProjectScope,
+    general::api_schema::{diff_routes, HttpMethod, RouteObject},
+};
+use serde::{Deserialize, Serialize};
+use std::{cmp::Reverse, env, fmt};
+
+pub const DEFAULT_CANDIDATES: usize = 1;
+
+// How many independent first drafts of the backend to generate and compare, from
+// BACKEND_CANDIDATES. One keeps the single-draft pipeline.
+pub fn candidates_from_env() -> usize {
+    env::var("BACKEND_CANDIDATES")
+        .ok()
+        .and_then(|count| count.parse().ok())
+        .filter(|count: &usize| *count > 0)
+        .unwrap_or(DEFAULT_CANDIDATES)
+}
+
+// How one candidate server did when it was built and tested in its own workspace.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CandidateScore {
+    pub candidate: usize,
+    pub compiles: bool,
+    pub errors: usize,
+    pub warnings: usize,
+    pub endpoints_passed: usize,
+    pub endpoints_tested: usize,
+    pub scope_matched: usize,
+    pub scope_checked: usize,
+    pub chosen: bool,
+    pub note: Option<String>,
+}
+
+impl CandidateScore {
+    pub fn unusable(candidate: usize, note: String) -> Self {
+        Self {
+            candidate,
+            note: Some(note),
+            ..Self::default()
+        }
+    }
+
+    // Compiling matters most, then working endpoints, then covering the scope, then clean code.
+    fn rank(&self) -> (bool, usize, usize, Reverse<usize>, Reverse<usize>) {
+        (
+            self.compiles,
+            self.endpoints_passed,
+            self.scope_matched,
+            Reverse(self.warnings),
+            Reverse(self.errors),
+        )
+    }
+}
+
+impl fmt::Display for CandidateScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(note) = &self.note {
+            return write!(f, "candidate {}: {}", self.candidate, note);
+        }
+        let build: String = match self.compiles {
+            true => "compiles".to_string(),
+            false => format!("{} errors", self.errors),
+        };
+        write!(
+            f,
+            "candidate {}: {}, {} warnings, {}/{} endpoints passing, {}/{} scope routes",
+            self.candidate,
+            build,
+            self.warnings,
+            self.endpoints_passed,
+            self.endpoints_tested,
+            self.scope_matched,
+            self.scope_checked
+        )
+    }
+}
+
+// The best scored candidate; ties go to the earlier one.
+pub fn best_candidate(scores: &[CandidateScore]) -> Option<usize> {
+    scores
+        .iter()
+        .filter(|score| score.note.is_none())
+        .max_by(|a, b| a.rank().cmp(&b.rank()).then(b.candidate.cmp(&a.candidate)))
+        .map(|score| score.candidate)
+}
+
+// How many of the routes the project needs a candidate serves. An API contract lists them;
+// otherwise the architect's scope asks for create, read, update and delete routes for CRUD and
+// login and logout routes for user accounts.
+pub fn scope_coverage(
+    routes: &[RouteObject],
+    project_scope: Option<&ProjectScope>,
+    api_contract: Option<&[RouteObject]>,
+) -> (usize, usize) {
+    if let Some(api_contract) = api_contract {
+        let missing: usize = diff_routes(api_contract, routes).missing.len();
+        return (api_contract.len() - missing, api_contract.len());
+    }
+
+    let has_method =
+        |methods: &[HttpMethod]| routes.iter().any(|route| methods.contains(&route.method));
+    let has_path = |segment: &str| {
+        routes
+            .iter()
+            .any(|route| route.route.to_lowercase().contains(segment))
+    };
+
+    let mut checks: Vec<bool> = vec![];
+    if let Some(project_scope) = project_scope {
+        if project_scope.is_crud_required {
+            checks.push(has_method(&[HttpMethod::Post]));
+            checks.push(has_method(&[HttpMethod::Get]));
+            checks.push(has_method(&[HttpMethod::Put, HttpMethod::Patch]));
+            checks.push(has_method(&[HttpMethod::Delete]));
+        }
+        if project_scope.is_user_login_and_logout {
+            checks.push(has_path("login"));
+            checks.push(has_path("logout"));
+        }
+    }
+    (checks.iter().filter(|check| **check).count(), checks.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(
+        candidate: usize,
+        compiles: bool,
+        endpoints_passed: usize,
+        warnings: usize,
+    ) -> CandidateScore {
+        CandidateScore {
+            candidate,
+            compiles,
+            endpoints_passed,
+            warnings,
+            ..CandidateScore::default()
+        }
+    }
+
+    #[test]
+    fn tests_best_candidate_prefers_working_endpoints_then_fewer_warnings() {
+        let scores: Vec<CandidateScore> = vec![
+            score(1, false, 0, 0),
+            score(2, true, 3, 4),
+            score(3, true, 3, 1),
+            score(4, true, 3, 1),
+            CandidateScore::unusable(5, "code unusable: empty manifest".to_string()),
+        ];
+        assert_eq!(best_candidate(&scores), Some(3));
+
+        assert_eq!(best_candidate(&scores[..1]), Some(1));
+        assert_eq!(best_candidate(&scores[4..]), None);
+        assert_eq!(
+            scores[1].to_string(),
+            "candidate 2: compiles, 4 warnings, 3/0 endpoints passing, 0/0 scope routes"
+        );
+    }
+
+    #[test]
+    fn tests_scope_coverage_checks_contract_or_architect_scope() {
+        let routes: Vec<RouteObject> = vec![
+            RouteObject::new(HttpMethod::Get, "/tasks", None, None),
+            RouteObject::new(HttpMethod::Post, "/tasks", None, None),
+            RouteObject::new(HttpMethod::Delete, "/tasks/{id}", None, None),
+            RouteObject::new(HttpMethod::Post, "/login", None, None),
+        ];
+        let project_scope: ProjectScope = ProjectScope {
+            is_crud_required: true,
+            is_user_login_and_logout: true,
+            is_external_urls_required: true,
+        };
+        assert_eq!(scope_coverage(&routes, Some(&project_scope), None), (4, 6));
+        assert_eq!(scope_coverage(&routes, None, None), (0, 0));
+
+        let api_contract: Vec<RouteObject> = vec![
+            RouteObject::new(HttpMethod::Get, "/tasks", None, None),
+            RouteObject::new(HttpMethod::Delete, "/tasks/{task_id}", None, None),
+            RouteObject::new(HttpMethod::Put, "/tasks/{task_id}", None, None),
+        ];
+        assert_eq!(
+            scope_coverage(&routes, Some(&project_scope), Some(&api_contract)),
+            (2, 3)
+        );
+    }
+}
@@ -0,0 +1,369 @@
+use crate::models::general::project_files::ProjectFiles;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+const CONTEXT_LINES: usize = 3;
+const MAX_ERRORS_SENT: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticLevel {
+    Error,
+    Warning,
+}
+
+// One rustc diagnostic from `cargo build --message-format=json`, located at its primary span.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub level: DiagnosticLevel,
+    pub code: Option<String>,
+    pub message: String,
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub label: Option<String>,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    fn from_message(message: &Value) -> Option<Self> {
+        let level: DiagnosticLevel = match message["level"].as_str()? {
+            "error" => DiagnosticLevel::Error,
+            "warning" => DiagnosticLevel::Warning,
+            _ => return None,
+        };
+        // Summaries such as "aborting due to 2 previous errors" point at no code.
+        let primary_span: &Value = message["spans"]
+            .as_array()?
+            .iter()
+            .find(|span| span["is_primary"].as_bool() == Some(true))?;
+
+        let notes: Vec<String> = message["children"]
+            .as_array()
+            .map(|children| children.iter().filter_map(child_note).collect())
+            .unwrap_or_default();
+
+        Some(Self {
+            level,
+            code: message["code"]["code"].as_str().map(String::from),
+            message: message["message"].as_str()?.to_string(),
+            file: primary_span["file_name"].as_str()?.to_string(),
+            line: primary_span["line_start"].as_u64()? as usize,
+            column: primary_span["column_start"].as_u64()? as usize,
+            label: primary_span["label"].as_str().map(String::from),
+            notes,
+        })
+    }
+
+    // Syntax errors and unresolved imports cause most of the errors after them, so they go first.
+    fn priority(&self) -> u8 {
+        match self.code.as_deref() {
+            None => 0,
+            Some("E0432" | "E0433") => 1,
+            Some("E0412" | "E0422" | "E0423" | "E0425") => 2,
+            Some(_) => 3,
+        }
+    }
+
+    // Renders like rustc, but with the surrounding lines of the file as it is now.
+    pub fn render_with_context(&self, files: &ProjectFiles) -> String {
+        let mut rendered: String = format!(
+            "{}\n  --> {}:{}:{}\n",
+            self, self.file, self.line, self.column
+        );
+
+        if let Some(source) = files.get(&self.file) {
+            let first_line: usize = self.line.saturating_sub(CONTEXT_LINES).max(1);
+            for (number, code) in source
+                .lines()
+                .enumerate()
+                .map(|(index, code)| (index + 1, code))
+                .skip(first_line - 1)
+                .take(self.line + CONTEXT_LINES + 1 - first_line)
+            {
+                let marker: char = if number == self.line { '>' } else { '|' };
+                rendered.push_str(&format!("{:>4} {} {}\n", number, marker, code));
+            }
+        }
+        if let Some(label) = &self.label {
+            rendered.push_str(&format!("     = {}\n", label));
+        }
+        for note in &self.notes {
+            rendered.push_str(&format!("     = {}\n", note));
+        }
+        rendered
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level: &str = match self.level {
+            DiagnosticLevel::Error => "error",
+            DiagnosticLevel::Warning => "warning",
+        };
+        match &self.code {
+            Some(code) => write!(f, "{}[{}]: {}", level, code, self.message),
+            None => write!(f, "{}: {}", level, self.message),
+        }
+    }
+}
+
+// Only the first line of help and notes, which is where the actionable part is; rustc's lists of
+// other trait implementations are not.
+fn child_note(child: &Value) -> Option<String> {
+    let level: &str = child["level"].as_str()?;
+    let message: &str = child["message"].as_str()?.lines().next()?;
+    let suggestion: Option<&str> = child["spans"]
+        .as_array()?
+        .iter()
+        .find_map(|span| span["suggested_replacement"].as_str())
+        .filter(|replacement| !replacement.is_empty());
+
+    match suggestion {
+        Some(replacement) => Some(format!("{}: {}: `{}`", level, message, replacement)),
+        None => Some(format!("{}: {}", level, message)),
+    }
+}
+
+// Reads the JSON lines cargo prints to stdout, ignoring everything that is not a diagnostic.
+pub fn parse_cargo_messages(output: &str) -> Vec<Diagnostic> {
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|message| message["reason"] == "compiler-message")
+        .filter_map(|message| Diagnostic::from_message(&message["message"]))
+        .collect()
+}
+
+// Errors before warnings, likely root causes before their follow-on errors, then source order.
+pub fn rank_diagnostics(mut diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+    diagnostics.sort_by(|a, b| {
+        (a.level, a.priority(), &a.file, a.line, a.column).cmp(&(
+            b.level,
+            b.priority(),
+            &b.file,
+            b.line,
+            b.column,
+        ))
+    });
+    diagnostics.dedup_by(|a, b| {
+        a.level == b.level
+            && a.code == b.code
+            && a.message == b.message
+            && a.file == b.file
+            && a.line == b.line
+            && a.column == b.column
+    });
+    diagnostics
+}
+
+// The ERROR_BUGS for the fix step: the highest ranked errors only, each with its code context.
+pub fn errors_for_fix(diagnostics: &[Diagnostic], files: &ProjectFiles) -> Vec<String> {
+    let errors: Vec<&Diagnostic> = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.level == DiagnosticLevel::Error)
+        .collect();
+
+    let mut details: Vec<String> = errors
+        .iter()
+        .take(MAX_ERRORS_SENT)
+        .map(|error| error.render_with_context(files))
+        .collect();
+    if errors.len() > MAX_ERRORS_SENT {
+        details.push(format!(
+            "... and {} more errors, most likely caused by the ones above",
+            errors.len() - MAX_ERRORS_SENT
+        ));
+    }
+    details
+}
+
+// The diagnostics of one `cargo build` in the fix loop, as recorded in the run output.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BuildDiagnostics {
+    pub build: usize,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl BuildDiagnostics {
+    pub fn count(&self, level: DiagnosticLevel) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.level == level)
+            .count()
+    }
+}
+
+// One rustfmt and clippy pass over a project that builds. Lints denied by the lint policy come
+// out of clippy as errors.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StyleReport {
+    pub round: usize,
+    pub reformatted_files: Vec<String>,
+    pub lints: Vec<Diagnostic>,
+}
+
+impl StyleReport {
+    pub fn count(&self, level: DiagnosticLevel) -> usize {
+        self.lints.iter().filter(|lint| lint.level == level).count()
+    }
+
+    // Warnings per lint, most frequent first.
+    pub fn warning_counts(&self) -> Vec<(String, usize)> {
+        let mut counts: Vec<(String, usize)> = vec![];
+        for lint in self
+            .lints
+            .iter()
+            .filter(|lint| lint.level == DiagnosticLevel::Warning)
+        {
+            let name: String = lint.code.clone().unwrap_or_else(|| "other".to_string());
+            match counts.iter_mut().find(|(counted, _)| *counted == name) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((name, 1)),
+            }
+        }
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn compiler_message(
+        level: &str,
+        code: Option<&str>,
+        message: &str,
+        line: usize,
+        children: Value,
+    ) -> String {
+        json!({
+            "reason": "compiler-message",
+            "message": {
+                "level": level,
+                "code": code.map(|code| json!({ "code": code })),
+                "message": message,
+                "spans": [
+                    { "file_name": "src/main.rs", "line_start": 1, "column_start": 1, "is_primary": false, "label": "defined here" },
+                    { "file_name": "src/main.rs", "line_start": line, "column_start": 5, "is_primary": true, "label": "here" }
+                ],
+                "children": children
+            }
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn tests_cargo_messages_are_parsed_deduplicated_and_ranked() {
+        let output: String = [
+            r#"{"reason":"compiler-artifact","target":{"name":"serde"}}"#.to_string(),
+            compiler_message("warning", Some("unused_imports"), "unused import: `HashMap`", 1, json!([])),
+            compiler_message("error", Some("E0308"), "mismatched types", 5, json!([])),
+            compiler_message(
+                "error",
+                Some("E0425"),
+                "cannot find function `totl` in this scope",
+                9,
+                json!([{ "level": "help", "message": "a function with a similar name exists", "spans": [{ "suggested_replacement": "total" }] }]),
+            ),
+            compiler_message("error", Some("E0308"), "mismatched types", 5, json!([])),
+            json!({ "reason": "compiler-message", "message": { "level": "error", "code": null, "message": "aborting due to 2 previous errors", "spans": [], "children": [] } }).to_string(),
+            r#"{"reason":"build-finished","success":false}"#.to_string(),
+        ]
+        .join("\n");
+
+        let diagnostics: Vec<Diagnostic> = rank_diagnostics(parse_cargo_messages(&output));
+
+        let summary: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            summary,
+            vec![
+                "error[E0425]: cannot find function `totl` in this scope",
+                "error[E0308]: mismatched types",
+                "warning[unused_imports]: unused import: `HashMap`",
+            ]
+        );
+        assert_eq!(
+            diagnostics[0].notes,
+            vec!["help: a function with a similar name exists: `total`"]
+        );
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (9, 5));
+        assert_eq!(diagnostics[0].label.as_deref(), Some("here"));
+    }
+
+    #[test]
+    fn tests_only_errors_are_sent_with_code_context() {
+        let mut files: ProjectFiles = ProjectFiles::default();
+        let source: String = (1..=12).map(|n| format!("line {}\n", n)).collect();
+        files.insert("src/main.rs", source).unwrap();
+
+        let error = |line: usize| Diagnostic {
+            level: DiagnosticLevel::Error,
+            code: Some("E0425".to_string()),
+            message: "cannot find value `missing` in this scope".to_string(),
+            file: "src/main.rs".to_string(),
+            line,
+            column: 20,
+            label: Some("not found in this scope".to_string()),
+            notes: vec![],
+        };
+        let warning: Diagnostic = Diagnostic {
+            level: DiagnosticLevel::Warning,
+            ..error(1)
+        };
+
+        let details: Vec<String> = errors_for_fix(&[error(2), warning], &files);
+        let expected: String = [
+            "error[E0425]: cannot find value `missing` in this scope",
+            "  --> src/main.rs:2:20",
+            "   1 | line 1",
+            "   2 > line 2",
+            "   3 | line 3",
+            "   4 | line 4",
+            "   5 | line 5",
+            "     = not found in this scope\n",
+        ]
+        .join("\n");
+        assert_eq!(details, vec![expected]);
+        assert_eq!(files.files_named_in(&details[0]), vec!["src/main.rs"]);
+
+        let style_report: StyleReport = StyleReport {
+            round: 1,
+            reformatted_files: vec![],
+            lints: vec![
+                Diagnostic {
+                    level: DiagnosticLevel::Warning,
+                    code: Some("clippy::needless_return".to_string()),
+                    ..error(3)
+                },
+                Diagnostic {
+                    level: DiagnosticLevel::Warning,
+                    code: Some("dead_code".to_string()),
+                    ..error(4)
+                },
+                Diagnostic {
+                    level: DiagnosticLevel::Warning,
+                    code: Some("clippy::needless_return".to_string()),
+                    ..error(5)
+                },
+                error(6),
+            ],
+        };
+        assert_eq!(style_report.count(DiagnosticLevel::Error), 1);
+        assert_eq!(
+            style_report.warning_counts(),
+            vec![
+                ("clippy::needless_return".to_string(), 2),
+                ("dead_code".to_string(), 1)
+            ]
+        );
+
+        let many: Vec<Diagnostic> = (1..=MAX_ERRORS_SENT + 2).map(error).collect();
+        let details: Vec<String> = errors_for_fix(&many, &files);
+        assert_eq!(details.len(), MAX_ERRORS_SENT + 1);
+        assert!(details[MAX_ERRORS_SENT].starts_with("... and 2 more errors"));
+    }
+}
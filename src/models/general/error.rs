@@ -0,0 +1,65 @@
+use std::{fmt, io};
+
+// Everything that can stop an agent or the project. `main` turns these into exit codes.
+#[derive(Debug)]
+pub enum AutoGptError {
+    Config(String),
+    Llm(String),
+    Decode {
+        operation: String,
+        reason: String,
+    },
+    Io {
+        action: &'static str,
+        source: io::Error,
+    },
+    Aborted,
+    Cancelled,
+    MissingInputs {
+        position: String,
+        fields: Vec<&'static str>,
+    },
+    AgentFailed {
+        position: String,
+        reason: String,
+    },
+}
+
+impl AutoGptError {
+    // For `map_err`, naming what was being done in the message.
+    pub fn io(action: &'static str) -> impl FnOnce(io::Error) -> Self {
+        move |source| Self::Io { action, source }
+    }
+
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::AgentFailed { .. } => 1,
+            Self::Config(_) | Self::MissingInputs { .. } => 2,
+            Self::Llm(_) | Self::Decode { .. } => 3,
+            Self::Io { .. } => 4,
+            Self::Aborted => 5,
+            Self::Cancelled => 130,
+        }
+    }
+}
+
+impl fmt::Display for AutoGptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Config(reason) => write!(f, "configuration error: {}", reason),
+            Self::Llm(reason) => write!(f, "model request failed: {}", reason),
+            Self::Decode { operation, reason } => {
+                write!(f, "could not decode the {} response: {}", operation, reason)
+            }
+            Self::Io { action, source } => write!(f, "failed to {}: {}", action, source),
+            Self::Aborted => write!(f, "stopped before running the generated code"),
+            Self::Cancelled => write!(f, "cancelled"),
+            Self::MissingInputs { position, fields } => {
+                write!(f, "{} cannot start without {}", position, fields.join(", "))
+            }
+            Self::AgentFailed { position, reason } => write!(f, "{} failed: {}", position, reason),
+        }
+    }
+}
+
+impl std::error::Error for AutoGptError {}
@@ -0,0 +1,187 @@
+use serde::Deserialize;
+use std::{env, fmt, fs};
+
+const BUILT_IN_HINTS: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/fix_hints.yaml"));
+const MAX_HINTS: usize = 5;
+
+#[derive(Debug, PartialEq)]
+pub enum FixHintsError {
+    Unreadable { path: String, message: String },
+    Invalid { source: String, message: String },
+}
+
+impl fmt::Display for FixHintsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unreadable { path, message } => {
+                write!(f, "cannot read fix hints '{}': {}", path, message)
+            }
+            Self::Invalid { source, message } => {
+                write!(f, "invalid fix hints in {}: {}", source, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FixHintsError {}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FixHint {
+    #[serde(default)]
+    pub code: Option<String>,
+    #[serde(default)]
+    pub message: Option<String>,
+    pub hint: String,
+}
+
+impl FixHint {
+    // Codes are matched the way diagnostics print them, `error[E0425]` or `warning[unused_imports]`.
+    fn matches(&self, error: &str) -> bool {
+        let has_code: bool = self
+            .code
+            .as_ref()
+            .is_none_or(|code| error.contains(&format!("[{}]", code)));
+        let has_message: bool = self
+            .message
+            .as_ref()
+            .is_none_or(|message| error.contains(message.as_str()));
+        has_code && has_message
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FixHints {
+    hints: Vec<FixHint>,
+}
+
+impl FixHints {
+    pub fn built_in() -> Self {
+        Self::parse(BUILT_IN_HINTS, "fix_hints.yaml").expect("Built-in fix hints are invalid")
+    }
+
+    // The built-in hints plus the ones in the file FIX_HINTS_FILE points at, which go first.
+    pub fn from_env() -> Result<Self, FixHintsError> {
+        let mut fix_hints: FixHints = Self::built_in();
+        if let Ok(path) = env::var("FIX_HINTS_FILE") {
+            let contents: String =
+                fs::read_to_string(&path).map_err(|e| FixHintsError::Unreadable {
+                    path: path.clone(),
+                    message: e.to_string(),
+                })?;
+            let mut extension: FixHints = Self::parse(&contents, &path)?;
+            extension.hints.append(&mut fix_hints.hints);
+            fix_hints = extension;
+        }
+        Ok(fix_hints)
+    }
+
+    pub fn parse(contents: &str, source: &str) -> Result<Self, FixHintsError> {
+        let invalid = |message: String| FixHintsError::Invalid {
+            source: source.to_string(),
+            message,
+        };
+
+        let hints: Vec<FixHint> =
+            serde_yaml::from_str(contents).map_err(|e| invalid(e.to_string()))?;
+        if let Some(hint) = hints
+            .iter()
+            .find(|hint| hint.code.is_none() && hint.message.is_none())
+        {
+            return Err(invalid(format!(
+                "hint '{}' has no code or message",
+                hint.hint
+            )));
+        }
+        Ok(Self { hints })
+    }
+
+    // Hints for any of the errors in an ERROR_BUGS text, in file order and without repeats.
+    pub fn for_errors(&self, error_bugs: &str) -> Vec<&str> {
+        let errors: Vec<String> = split_errors(error_bugs);
+        let mut matched: Vec<&str> = vec![];
+
+        for fix_hint in &self.hints {
+            if matched.len() == MAX_HINTS {
+                break;
+            }
+            if errors.iter().any(|error| fix_hint.matches(error))
+                && !matched.contains(&fix_hint.hint.as_str())
+            {
+                matched.push(&fix_hint.hint);
+            }
+        }
+        matched
+    }
+}
+
+// Each compiler diagnostic starts a new block, so a hint needs its code and message in the same
+// error. Runtime failures stay one block.
+fn split_errors(error_bugs: &str) -> Vec<String> {
+    let mut errors: Vec<String> = vec![String::new()];
+    for line in error_bugs.lines() {
+        if line.starts_with("error") || line.starts_with("warning") {
+            errors.push(String::new());
+        }
+        let error: &mut String = errors.last_mut().unwrap();
+        error.push_str(line);
+        error.push('\n');
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tests_hints_match_code_and_message_within_one_error() {
+        let fix_hints: FixHints = FixHints::built_in();
+        let error_bugs: &str = "The project does not compile:\n\
+            error[E0425]: cannot find value `db` in this scope\n  \
+            --> src/main.rs:12:5\n\
+            error[E0277]: the trait bound `Task: Deserialize<'_>` is not satisfied\n  \
+            --> src/main.rs:30:20\n\
+            error[E0425]: cannot find value `tasks` in this scope\n";
+
+        let hints: Vec<&str> = fix_hints.for_errors(error_bugs);
+        assert_eq!(hints.len(), 2);
+        assert!(hints[0].starts_with("A name is not in scope"));
+        assert!(hints[1].contains("from JSON request bodies"));
+
+        // The FromRequest hint needs both parts in the same error.
+        let split: &str = "error[E0277]: `Task` is not a future\n\
+            error[E0599]: no method `from_request` named FromRequest\n";
+        assert!(!fix_hints
+            .for_errors(split)
+            .iter()
+            .any(|hint| hint.contains("actix extractor")));
+
+        let panic: &str = "The server exited while it was being tested:\n\
+            SERVER STDERR:\nthread 'main' panicked at src/main.rs:40:9:\n\
+            called `Option::unwrap()` on a `None` value\n";
+        assert_eq!(fix_hints.for_errors(panic).len(), 1);
+    }
+
+    #[test]
+    fn tests_extension_hints_are_validated() {
+        let extension: FixHints = FixHints::parse(
+            "- code: E0599\n  hint: Use the task store helpers.\n",
+            "extra.yaml",
+        )
+        .unwrap();
+        assert_eq!(
+            extension.for_errors("error[E0599]: no method named `save`"),
+            vec!["Use the task store helpers."]
+        );
+
+        assert_eq!(
+            FixHints::parse("- hint: Always applies\n", "extra.yaml"),
+            Err(FixHintsError::Invalid {
+                source: "extra.yaml".to_string(),
+                message: "hint 'Always applies' has no code or message".to_string(),
+            })
+        );
+        assert!(FixHints::parse("- code: E0599\n  hnit: typo\n", "extra.yaml").is_err());
+    }
+}
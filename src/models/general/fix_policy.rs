@@ -0,0 +1,127 @@
+use std::{env, fmt, str::FromStr};
+
+const DEFAULT_MAX_ITERATIONS: u8 = 3;
+const DEFAULT_ESCALATIONS: &str = "stronger_model,rollback,regenerate";
+const DEFAULT_STRONGER_MODEL: &str = "gpt-4o";
+
+// What the backend developer tries once its fix attempts run out, in the order configured.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Escalation {
+    StrongerModel,
+    Regenerate,
+    Rollback,
+}
+
+impl FromStr for Escalation {
+    type Err = String;
+
+    fn from_str(escalation: &str) -> Result<Self, Self::Err> {
+        match escalation.trim() {
+            "stronger_model" => Ok(Self::StrongerModel),
+            "regenerate" => Ok(Self::Regenerate),
+            "rollback" => Ok(Self::Rollback),
+            _ => Err(format!(
+                "unknown escalation '{}', expected stronger_model, regenerate or rollback",
+                escalation
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Escalation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let escalation: &str = match self {
+            Self::StrongerModel => "stronger_model",
+            Self::Regenerate => "regenerate",
+            Self::Rollback => "rollback",
+        };
+        write!(f, "{}", escalation)
+    }
+}
+
+// How many fixes to attempt before escalating, from BACKEND_FIX_MAX_ITERATIONS, and the
+// escalations to go through, from the comma separated BACKEND_FIX_ESCALATION ("" for none).
+// Each escalation starts a new round of attempts; when none are left the agent gives up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixPolicy {
+    pub max_iterations: u8,
+    pub escalations: Vec<Escalation>,
+    pub stronger_model: String,
+}
+
+impl Default for FixPolicy {
+    fn default() -> Self {
+        Self {
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+            escalations: parse_escalations(DEFAULT_ESCALATIONS)
+                .expect("Default escalations are invalid"),
+            stronger_model: DEFAULT_STRONGER_MODEL.to_string(),
+        }
+    }
+}
+
+impl FixPolicy {
+    pub fn from_env() -> Result<Self, String> {
+        let mut fix_policy: FixPolicy = Self::default();
+
+        if let Ok(max_iterations) = env::var("BACKEND_FIX_MAX_ITERATIONS") {
+            fix_policy.max_iterations = max_iterations
+                .trim()
+                .parse()
+                .ok()
+                .filter(|max_iterations: &u8| *max_iterations > 0)
+                .ok_or_else(|| {
+                    format!(
+                        "BACKEND_FIX_MAX_ITERATIONS must be a positive number, got '{}'",
+                        max_iterations
+                    )
+                })?;
+        }
+        if let Ok(escalations) = env::var("BACKEND_FIX_ESCALATION") {
+            fix_policy.escalations = parse_escalations(&escalations)?;
+        }
+        if let Ok(stronger_model) = env::var("BACKEND_FIX_STRONGER_MODEL") {
+            fix_policy.stronger_model = stronger_model;
+        }
+        Ok(fix_policy)
+    }
+
+    // The model for fix steps once `escalations` escalations have been used.
+    pub fn fix_model(&self, escalations: usize) -> Option<&str> {
+        self.escalations
+            .iter()
+            .take(escalations)
+            .any(|escalation| *escalation == Escalation::StrongerModel)
+            .then_some(self.stronger_model.as_str())
+    }
+}
+
+pub fn parse_escalations(escalations: &str) -> Result<Vec<Escalation>, String> {
+    escalations
+        .split(',')
+        .filter(|escalation| !escalation.trim().is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tests_escalations_are_parsed_in_order() {
+        assert_eq!(
+            parse_escalations("rollback, stronger_model").unwrap(),
+            vec![Escalation::Rollback, Escalation::StrongerModel]
+        );
+        assert_eq!(parse_escalations("").unwrap(), vec![]);
+        assert!(parse_escalations("rollback,retry").is_err());
+
+        let fix_policy: FixPolicy = FixPolicy {
+            escalations: vec![Escalation::Rollback, Escalation::StrongerModel],
+            ..FixPolicy::default()
+        };
+        assert_eq!(fix_policy.fix_model(1), None);
+        assert_eq!(fix_policy.fix_model(2), Some(DEFAULT_STRONGER_MODEL));
+    }
+}
@@ -1 +1,10 @@
+pub mod api_schema;
+pub mod bug_report;
+pub mod candidate_score;
+pub mod diagnostics;
+pub mod error;
+pub mod fix_hints;
+pub mod fix_policy;
 pub mod llm;
+pub mod openapi;
+pub mod project_files;
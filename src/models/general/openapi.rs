@@ -0,0 +1,574 @@
+use crate::models::general::api_schema::{openapi_path, FieldType, HttpMethod, RouteObject};
+use serde_json::{json, Map, Value};
+use std::{collections::BTreeMap, fmt, fs, path::Path};
+
+pub const OPENAPI_VERSION: &str = "3.1.0";
+const JSON_CONTENT_TYPE: &str = "application/json";
+const MAX_REF_DEPTH: usize = 16;
+
+#[derive(Debug, PartialEq)]
+pub enum OpenApiError {
+    Unreadable(String),
+    Invalid(String),
+    NoRoutes,
+}
+
+impl fmt::Display for OpenApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unreadable(e) => write!(f, "could not read OpenAPI document: {}", e),
+            Self::Invalid(reason) => write!(f, "invalid OpenAPI document: {}", reason),
+            Self::NoRoutes => write!(f, "OpenAPI document has no operations"),
+        }
+    }
+}
+
+impl std::error::Error for OpenApiError {}
+
+// Renders the endpoints as an OpenAPI 3.1 document. Path parameters are typed as strings
+// since the route template carries no type information.
+pub fn openapi_document(title: &str, description: &str, routes: &[RouteObject]) -> Value {
+    let mut paths: Map<String, Value> = Map::new();
+
+    for route in routes {
+        let path_item: &mut Value = paths
+            .entry(openapi_path(&route.route))
+            .or_insert_with(|| Value::Object(Map::new()));
+        path_item[route.method.as_str()] = operation(route);
+    }
+
+    json!({
+        "openapi": OPENAPI_VERSION,
+        "info": {
+            "title": title,
+            "description": description,
+            "version": "0.1.0"
+        },
+        "paths": paths
+    })
+}
+
+fn operation(route: &RouteObject) -> Value {
+    let mut operation: Map<String, Value> = Map::new();
+    operation.insert("operationId".to_string(), json!(operation_id(route)));
+
+    if !route.path_params.is_empty() {
+        let parameters: Vec<Value> = route
+            .path_params
+            .iter()
+            .map(|name| {
+                json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": {"type": "string"}
+                })
+            })
+            .collect();
+        operation.insert("parameters".to_string(), Value::Array(parameters));
+    }
+
+    if let Some(request_body) = &route.request_body {
+        operation.insert(
+            "requestBody".to_string(),
+            json!({
+                "required": true,
+                "content": {JSON_CONTENT_TYPE: {"schema": json_schema(request_body)}}
+            }),
+        );
+    }
+
+    let response: Value = match &route.response {
+        Some(response) => json!({
+            "description": "Successful response",
+            "content": {JSON_CONTENT_TYPE: {"schema": json_schema(response)}}
+        }),
+        None => json!({"description": "Successful response"}),
+    };
+    operation.insert("responses".to_string(), json!({"200": response}));
+
+    Value::Object(operation)
+}
+
+// "get" + "/item/{id}" -> "get_item_id"
+fn operation_id(route: &RouteObject) -> String {
+    let mut operation_id: String = route.method.as_str().to_string();
+    for segment in openapi_path(&route.route).split(|c: char| !c.is_ascii_alphanumeric()) {
+        if !segment.is_empty() {
+            operation_id.push('_');
+            operation_id.push_str(segment);
+        }
+    }
+    operation_id
+}
+
+pub fn json_schema(field_type: &FieldType) -> Value {
+    match field_type {
+        FieldType::String => json!({"type": "string"}),
+        FieldType::Number => json!({"type": "number"}),
+        FieldType::Bool => json!({"type": "boolean"}),
+        FieldType::Any => json!({}),
+        FieldType::Array(item) => json!({"type": "array", "items": json_schema(item)}),
+        FieldType::Object(fields) => {
+            let properties: Map<String, Value> = fields
+                .iter()
+                .map(|(name, field)| (name.clone(), json_schema(field)))
+                .collect();
+            let required: Vec<&String> = fields
+                .iter()
+                .filter(|(_, field)| !matches!(field, FieldType::Optional(_)))
+                .map(|(name, _)| name)
+                .collect();
+            json!({"type": "object", "properties": properties, "required": required})
+        }
+        FieldType::Optional(inner) => {
+            json!({"anyOf": [json_schema(inner), {"type": "null"}]})
+        }
+    }
+}
+
+// Reads a YAML or JSON OpenAPI 3.x document. YAML is a superset of JSON so one parser does both.
+pub fn load_openapi_routes(path: &Path) -> Result<Vec<RouteObject>, OpenApiError> {
+    let contents: String =
+        fs::read_to_string(path).map_err(|e| OpenApiError::Unreadable(e.to_string()))?;
+    let document: Value =
+        serde_yaml::from_str(&contents).map_err(|e| OpenApiError::Unreadable(e.to_string()))?;
+    routes_from_openapi(&document)
+}
+
+pub fn routes_from_openapi(document: &Value) -> Result<Vec<RouteObject>, OpenApiError> {
+    let version: &str = document["openapi"]
+        .as_str()
+        .ok_or_else(|| OpenApiError::Invalid("missing 'openapi' version".to_string()))?;
+    if !version.starts_with("3.") {
+        return Err(OpenApiError::Invalid(format!(
+            "unsupported version '{}'",
+            version
+        )));
+    }
+
+    let paths: &Map<String, Value> = document["paths"]
+        .as_object()
+        .ok_or_else(|| OpenApiError::Invalid("missing 'paths'".to_string()))?;
+
+    let mut routes: Vec<RouteObject> = vec![];
+    for (path, path_item) in paths {
+        let Some(operations) = path_item.as_object() else {
+            return Err(OpenApiError::Invalid(format!(
+                "path '{}' is not an object",
+                path
+            )));
+        };
+
+        // Path items also hold keys like "parameters" or "summary" next to the operations.
+        for (method, operation) in operations {
+            let Ok(method) = method.parse::<HttpMethod>() else {
+                continue;
+            };
+
+            let request_body: Option<FieldType> =
+                json_content_schema(&operation["requestBody"], document)
+                    .map(|schema| field_type_from_schema(schema, document, 0));
+            routes.push(RouteObject::new(
+                method,
+                path,
+                request_body,
+                success_response(operation)
+                    .and_then(|response| json_content_schema(response, document))
+                    .map(|schema| field_type_from_schema(schema, document, 0)),
+            ));
+        }
+    }
+
+    if routes.is_empty() {
+        return Err(OpenApiError::NoRoutes);
+    }
+    Ok(routes)
+}
+
+// Prefers 200, then the lowest other 2xx status.
+fn success_response(operation: &Value) -> Option<&Value> {
+    let responses: &Map<String, Value> = operation["responses"].as_object()?;
+    responses.get("200").or_else(|| {
+        responses
+            .iter()
+            .filter(|(status, _)| status.starts_with('2'))
+            .min_by_key(|(status, _)| status.as_str())
+            .map(|(_, response)| response)
+    })
+}
+
+fn json_content_schema<'a>(body: &'a Value, document: &'a Value) -> Option<&'a Value> {
+    let content: &Map<String, Value> = resolve_ref(body, document)["content"].as_object()?;
+    content
+        .get(JSON_CONTENT_TYPE)
+        .or_else(|| content.values().next())
+        .map(|media_type| &media_type["schema"])
+        .filter(|schema| !schema.is_null())
+}
+
+// Request bodies and responses can be shared from `#/components/...`, possibly through another
+// reference. An unresolvable one is returned as is.
+fn resolve_ref<'a>(value: &'a Value, document: &'a Value) -> &'a Value {
+    let mut resolved: &Value = value;
+    for _ in 0..MAX_REF_DEPTH {
+        match resolved["$ref"]
+            .as_str()
+            .and_then(|reference| reference.strip_prefix('#'))
+            .and_then(|pointer| document.pointer(pointer))
+        {
+            Some(target) => resolved = target,
+            None => break,
+        }
+    }
+    resolved
+}
+
+pub fn field_type_from_schema(schema: &Value, document: &Value, depth: usize) -> FieldType {
+    if depth > MAX_REF_DEPTH {
+        return FieldType::Any;
+    }
+
+    if let Some(reference) = schema["$ref"].as_str() {
+        return match reference
+            .strip_prefix('#')
+            .and_then(|pointer| document.pointer(pointer))
+        {
+            Some(target) => field_type_from_schema(target, document, depth + 1),
+            None => FieldType::Any,
+        };
+    }
+
+    for combinator in ["anyOf", "oneOf"] {
+        if let Some(variants) = schema[combinator].as_array() {
+            let is_null = |variant: &&Value| variant["type"] == "null";
+            let field_type: FieldType = variants
+                .iter()
+                .find(|variant| !is_null(variant))
+                .map(|variant| field_type_from_schema(variant, document, depth + 1))
+                .unwrap_or(FieldType::Any);
+            return if variants.iter().any(|variant| is_null(&variant)) {
+                FieldType::Optional(Box::new(field_type))
+            } else {
+                field_type
+            };
+        }
+    }
+
+    // 3.1 writes nullable types as ["string", "null"], 3.0 uses "nullable: true".
+    let (type_name, is_nullable): (Option<&str>, bool) = match &schema["type"] {
+        Value::String(type_name) => (Some(type_name.as_str()), false),
+        Value::Array(types) => (
+            types
+                .iter()
+                .filter_map(Value::as_str)
+                .find(|type_name| *type_name != "null"),
+            types.iter().any(|type_name| type_name == "null"),
+        ),
+        _ if schema.get("properties").is_some() => (Some("object"), false),
+        _ => (None, false),
+    };
+
+    let field_type: FieldType = match type_name {
+        Some("string") => FieldType::String,
+        Some("number") | Some("integer") => FieldType::Number,
+        Some("boolean") => FieldType::Bool,
+        Some("array") => FieldType::Array(Box::new(field_type_from_schema(
+            &schema["items"],
+            document,
+            depth + 1,
+        ))),
+        Some("object") => {
+            let required: Vec<&str> = schema["required"]
+                .as_array()
+                .map(|required| required.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            let properties: BTreeMap<String, FieldType> = schema["properties"]
+                .as_object()
+                .map(|properties| {
+                    properties
+                        .iter()
+                        .map(|(name, property)| {
+                            let property_type: FieldType =
+                                field_type_from_schema(property, document, depth + 1);
+                            let property_type: FieldType = match property_type {
+                                FieldType::Optional(_) => property_type,
+                                _ if !required.contains(&name.as_str()) => {
+                                    FieldType::Optional(Box::new(property_type))
+                                }
+                                _ => property_type,
+                            };
+                            (name.clone(), property_type)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            FieldType::Object(properties)
+        }
+        _ => FieldType::Any,
+    };
+
+    if is_nullable || schema["nullable"] == true {
+        FieldType::Optional(Box::new(field_type))
+    } else {
+        field_type
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn routes() -> Vec<RouteObject> {
+        let item: FieldType = FieldType::Object(BTreeMap::from([
+            ("id".to_string(), FieldType::Number),
+            ("name".to_string(), FieldType::String),
+            (
+                "done".to_string(),
+                FieldType::Optional(Box::new(FieldType::Bool)),
+            ),
+        ]));
+
+        vec![
+            RouteObject::new(
+                HttpMethod::Get,
+                "/items",
+                None,
+                Some(FieldType::Array(Box::new(item.clone()))),
+            ),
+            RouteObject::new(HttpMethod::Post, "/items", Some(item.clone()), None),
+            RouteObject::new(HttpMethod::Get, "/items/{id}", None, Some(item)),
+            RouteObject::new(HttpMethod::Delete, "/items/{id}", None, None),
+        ]
+    }
+
+    // Checks the parts of the OpenAPI 3.1 structure we rely on: every templated segment has a
+    // matching required path parameter, every operation has a response, and bodies are JSON.
+    fn assert_valid_openapi(document: &Value) {
+        assert_eq!(document["openapi"], OPENAPI_VERSION);
+        assert!(document["info"]["title"].is_string());
+        assert!(document["info"]["version"].is_string());
+
+        let paths: &Map<String, Value> = document["paths"].as_object().unwrap();
+        for (path, path_item) in paths {
+            assert!(path.starts_with('/'));
+            let template_params: Vec<String> =
+                crate::models::general::api_schema::parse_path_params(path);
+
+            for (method, operation) in path_item.as_object().unwrap() {
+                assert!(method.parse::<HttpMethod>().is_ok());
+                assert!(operation["operationId"].is_string());
+                assert!(operation["responses"]["200"]["description"].is_string());
+
+                let parameters: Vec<&str> = operation["parameters"]
+                    .as_array()
+                    .map(|parameters| {
+                        parameters
+                            .iter()
+                            .inspect(|parameter| {
+                                assert_eq!(parameter["in"], "path");
+                                assert_eq!(parameter["required"], true);
+                            })
+                            .map(|parameter| parameter["name"].as_str().unwrap())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                assert_eq!(parameters, template_params);
+
+                if let Some(request_body) = operation.get("requestBody") {
+                    assert!(request_body["content"][JSON_CONTENT_TYPE]["schema"].is_object());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tests_openapi_document_is_structurally_valid() {
+        let document: Value = openapi_document("web_gpt_server", "todo list api", &routes());
+        assert_valid_openapi(&document);
+
+        assert_eq!(
+            document["paths"]
+                .as_object()
+                .unwrap()
+                .keys()
+                .collect::<Vec<_>>(),
+            vec!["/items", "/items/{id}"]
+        );
+        assert_eq!(
+            document["paths"]["/items/{id}"]["delete"]["operationId"],
+            "delete_items_id"
+        );
+        assert_eq!(
+            document["paths"]["/items"]["get"]["responses"]["200"]["content"][JSON_CONTENT_TYPE]
+                ["schema"]["items"]["required"],
+            json!(["id", "name"])
+        );
+        assert_eq!(
+            document["paths"]["/items"]["post"]["requestBody"]["content"][JSON_CONTENT_TYPE]
+                ["schema"]["properties"]["done"],
+            json!({"anyOf": [{"type": "boolean"}, {"type": "null"}]})
+        );
+        assert!(
+            document["paths"]["/items/{id}"]["delete"]["responses"]["200"]
+                .get("content")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn tests_openapi_paths_leave_out_parameter_patterns() {
+        let routes: Vec<RouteObject> = vec![RouteObject::new(
+            HttpMethod::Get,
+            "/items/{id:[0-9]+}",
+            None,
+            None,
+        )];
+        let document: Value = openapi_document("web_gpt_server", "todo list api", &routes);
+        assert_valid_openapi(&document);
+
+        let operation: &Value = &document["paths"]["/items/{id}"]["get"];
+        assert_eq!(operation["operationId"], "get_items_id");
+        assert_eq!(operation["parameters"][0]["name"], "id");
+    }
+
+    #[test]
+    fn tests_openapi_yaml_matches_json() {
+        let document: Value = openapi_document("web_gpt_server", "todo list api", &routes());
+        let yaml: String = serde_yaml::to_string(&document).unwrap();
+
+        assert!(yaml.lines().any(|line| line == "openapi: 3.1.0"));
+        let parsed: Value = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed, document);
+        assert_valid_openapi(&parsed);
+    }
+
+    #[test]
+    fn tests_exported_document_imports_back_to_the_same_routes() {
+        let document: Value = openapi_document("web_gpt_server", "todo list api", &routes());
+        let mut imported: Vec<RouteObject> = routes_from_openapi(&document).unwrap();
+        let mut expected: Vec<RouteObject> = routes();
+        for routes in [&mut imported, &mut expected] {
+            routes.sort_by(|a, b| (&a.route, a.method).cmp(&(&b.route, b.method)));
+        }
+        assert_eq!(imported, expected);
+    }
+
+    #[test]
+    fn tests_contract_with_refs_and_nullable_fields_is_imported() {
+        let spec: &str = r##"
+openapi: 3.0.3
+info: {title: Todos, version: "1"}
+paths:
+  /todos/{todoId}:
+    parameters:
+      - {name: todoId, in: path, required: true, schema: {type: integer}}
+    get:
+      responses:
+        "200":
+          description: ok
+          content:
+            application/json:
+              schema: {$ref: "#/components/schemas/Todo"}
+    patch:
+      requestBody:
+        content:
+          application/json:
+            schema:
+              type: object
+              properties:
+                title: {type: string, nullable: true}
+      responses:
+        "204": {description: updated}
+components:
+  schemas:
+    Todo:
+      type: object
+      required: [id, title]
+      properties:
+        id: {type: integer}
+        title: {type: string}
+        tags: {type: array, items: {type: string}}
+"##;
+        let document: Value = serde_yaml::from_str(spec).unwrap();
+        let routes: Vec<RouteObject> = routes_from_openapi(&document).unwrap();
+
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes[0].method, HttpMethod::Get);
+        assert_eq!(routes[0].path_params, vec!["todoId".to_string()]);
+        assert_eq!(
+            routes[0].response,
+            Some(FieldType::Object(BTreeMap::from([
+                ("id".to_string(), FieldType::Number),
+                ("title".to_string(), FieldType::String),
+                (
+                    "tags".to_string(),
+                    FieldType::Optional(Box::new(FieldType::Array(Box::new(FieldType::String))))
+                ),
+            ])))
+        );
+        assert_eq!(routes[1].method, HttpMethod::Patch);
+        assert_eq!(
+            routes[1].request_body,
+            Some(FieldType::Object(BTreeMap::from([(
+                "title".to_string(),
+                FieldType::Optional(Box::new(FieldType::String))
+            )])))
+        );
+        assert_eq!(routes[1].response, None);
+    }
+
+    #[test]
+    fn tests_referenced_request_bodies_and_responses_are_imported() {
+        let spec: &str = r##"
+openapi: 3.0.3
+info: {title: Todos, version: "1"}
+paths:
+  /todos:
+    post:
+      requestBody: {$ref: "#/components/requestBodies/NewTodo"}
+      responses:
+        "201": {$ref: "#/components/responses/Created"}
+components:
+  requestBodies:
+    NewTodo:
+      content:
+        application/json:
+          schema:
+            type: object
+            required: [title]
+            properties:
+              title: {type: string}
+  responses:
+    Created: {$ref: "#/components/responses/TodoId"}
+    TodoId:
+      description: created
+      content:
+        application/json:
+          schema: {type: integer}
+"##;
+        let document: Value = serde_yaml::from_str(spec).unwrap();
+        let routes: Vec<RouteObject> = routes_from_openapi(&document).unwrap();
+
+        assert_eq!(
+            routes[0].request_body,
+            Some(FieldType::Object(BTreeMap::from([(
+                "title".to_string(),
+                FieldType::String
+            )])))
+        );
+        assert_eq!(routes[0].response, Some(FieldType::Number));
+    }
+
+    #[test]
+    fn tests_unsupported_documents_are_rejected() {
+        assert!(matches!(
+            routes_from_openapi(&json!({"swagger": "2.0", "paths": {}})),
+            Err(OpenApiError::Invalid(_))
+        ));
+        assert_eq!(
+            routes_from_openapi(&json!({"openapi": "3.1.0", "paths": {"/": {"summary": "x"}}})),
+            Err(OpenApiError::NoRoutes)
+        );
+    }
+}
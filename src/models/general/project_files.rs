@@ -0,0 +1,287 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt, path::Component, path::Path};
+
+const FILE_START: &str = "=== FILE: ";
+const FILE_MARKER_END: &str = " ===";
+const FILE_END: &str = "=== END FILE ===";
+
+pub const MAIN_FILE: &str = "src/main.rs";
+
+#[derive(Debug, PartialEq)]
+pub enum ManifestError {
+    Empty,
+    UnsafePath(String),
+    UnterminatedFile(String),
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "manifest contains no files"),
+            Self::UnsafePath(path) => write!(f, "manifest path '{}' is not a src/*.rs file", path),
+            Self::UnterminatedFile(path) => {
+                write!(f, "manifest file '{}' is missing '{}'", path, FILE_END)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ManifestError {}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(transparent)]
+pub struct ProjectFiles {
+    files: BTreeMap<String, String>,
+}
+
+impl ProjectFiles {
+    // Parses the manifest format the backend AI functions print:
+    //   === FILE: src/main.rs ===
+    //   ...code...
+    //   === END FILE ===
+    // Output without any file markers is treated as a single file at `fallback_path`.
+    pub fn from_manifest(manifest: &str, fallback_path: &str) -> Result<Self, ManifestError> {
+        let mut files: BTreeMap<String, String> = BTreeMap::new();
+        let mut current: Option<(String, Vec<&str>)> = None;
+
+        for line in manifest.lines() {
+            match current.take() {
+                None => {
+                    let trimmed: &str = line.trim();
+                    if let Some(path) = trimmed
+                        .strip_prefix(FILE_START)
+                        .and_then(|rest| rest.strip_suffix(FILE_MARKER_END))
+                    {
+                        current = Some((validate_path(path.trim())?, vec![]));
+                    }
+                }
+                Some((path, mut lines)) => {
+                    if line.trim() == FILE_END {
+                        files.insert(path, join_code_lines(&lines));
+                    } else {
+                        lines.push(line);
+                        current = Some((path, lines));
+                    }
+                }
+            }
+        }
+
+        if let Some((path, _)) = current {
+            return Err(ManifestError::UnterminatedFile(path));
+        }
+
+        if files.is_empty() {
+            let code: String = join_code_lines(&manifest.lines().collect::<Vec<&str>>());
+            if code.trim().is_empty() {
+                return Err(ManifestError::Empty);
+            }
+            files.insert(validate_path(fallback_path)?, code);
+        }
+
+        Ok(Self { files })
+    }
+
+    pub fn to_manifest(&self) -> String {
+        self.files
+            .iter()
+            .map(|(path, contents)| render_file(path, contents))
+            .collect()
+    }
+
+    pub fn subset_manifest(&self, paths: &[String]) -> String {
+        self.files
+            .iter()
+            .filter(|(path, _)| paths.contains(path))
+            .map(|(path, contents)| render_file(path, contents))
+            .collect()
+    }
+
+    pub fn insert(&mut self, path: &str, contents: String) -> Result<(), ManifestError> {
+        self.files.insert(validate_path(path)?, contents);
+        Ok(())
+    }
+
+    pub fn merge(&mut self, other: ProjectFiles) {
+        self.files.extend(other.files);
+    }
+
+    pub fn get(&self, path: &str) -> Option<&String> {
+        self.files.get(path)
+    }
+
+    pub fn paths(&self) -> Vec<String> {
+        self.files.keys().cloned().collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.files.iter()
+    }
+
+    // Understands compiler locations (`--> src/x.rs:4:5`) and panics (`panicked at src/x.rs:4:5`).
+    pub fn files_named_in(&self, error_output: &str) -> Vec<String> {
+        let mut named: Vec<String> = vec![];
+
+        for line in error_output.lines() {
+            let location: Option<&str> = line.trim().strip_prefix("--> ").or_else(|| {
+                line.split_once("panicked at ")
+                    .map(|(_, location)| location)
+            });
+            let Some(location) = location else {
+                continue;
+            };
+            let path: &str = location.split(':').next().unwrap_or_default();
+            if self.files.contains_key(path) && !named.iter().any(|named| named == path) {
+                named.push(path.to_string());
+            }
+        }
+
+        named
+    }
+}
+
+fn validate_path(path: &str) -> Result<String, ManifestError> {
+    let relative: &Path = Path::new(path);
+    let is_safe: bool = relative.starts_with("src")
+        && relative
+            .extension()
+            .is_some_and(|extension| extension == "rs")
+        && relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+
+    if is_safe {
+        Ok(path.to_string())
+    } else {
+        Err(ManifestError::UnsafePath(path.to_string()))
+    }
+}
+
+// Drops the markdown code fences models like to wrap code in.
+fn join_code_lines(lines: &[&str]) -> String {
+    let code_lines: Vec<&str> = lines
+        .iter()
+        .filter(|line| !line.trim_start().starts_with("```"))
+        .copied()
+        .collect();
+
+    let mut code: String = code_lines.join("\n");
+    code.push('\n');
+    code
+}
+
+fn render_file(path: &str, contents: &str) -> String {
+    format!(
+        "{}{}{}\n{}\n{}\n",
+        FILE_START,
+        path,
+        FILE_MARKER_END,
+        contents.trim_end(),
+        FILE_END
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tests_manifest_round_trip() {
+        let manifest: &str = "Here you go:\n\
+            === FILE: src/main.rs ===\n\
+            ```rust\n\
+            mod handlers;\n\
+            fn main() {}\n\
+            ```\n\
+            === END FILE ===\n\
+            === FILE: src/handlers.rs ===\n\
+            pub fn index() {}\n\
+            === END FILE ===\n";
+
+        let files: ProjectFiles = ProjectFiles::from_manifest(manifest, MAIN_FILE).unwrap();
+        assert_eq!(
+            files.paths(),
+            vec!["src/handlers.rs".to_string(), "src/main.rs".to_string()]
+        );
+        assert_eq!(
+            files.files.get("src/main.rs").unwrap(),
+            "mod handlers;\nfn main() {}\n"
+        );
+
+        let reparsed: ProjectFiles =
+            ProjectFiles::from_manifest(&files.to_manifest(), MAIN_FILE).unwrap();
+        assert_eq!(reparsed, files);
+    }
+
+    #[test]
+    fn tests_plain_code_becomes_main_file() {
+        let files: ProjectFiles = ProjectFiles::from_manifest("fn main() {}", MAIN_FILE).unwrap();
+        assert_eq!(files.paths(), vec![MAIN_FILE.to_string()]);
+
+        let files: ProjectFiles =
+            ProjectFiles::from_manifest("pub fn db() {}", "src/db.rs").unwrap();
+        assert_eq!(files.paths(), vec!["src/db.rs".to_string()]);
+
+        assert_eq!(
+            ProjectFiles::from_manifest("  ", MAIN_FILE),
+            Err(ManifestError::Empty)
+        );
+    }
+
+    #[test]
+    fn tests_manifest_rejects_bad_files() {
+        assert_eq!(
+            ProjectFiles::from_manifest(
+                "=== FILE: ../Cargo.toml ===\n\n=== END FILE ===",
+                MAIN_FILE
+            ),
+            Err(ManifestError::UnsafePath("../Cargo.toml".to_string()))
+        );
+        assert_eq!(
+            ProjectFiles::from_manifest(
+                "=== FILE: src/../../evil.rs ===\n\n=== END FILE ===",
+                MAIN_FILE
+            ),
+            Err(ManifestError::UnsafePath("src/../../evil.rs".to_string()))
+        );
+        assert_eq!(
+            ProjectFiles::from_manifest("=== FILE: src/db.rs ===\nfn main() {}", MAIN_FILE),
+            Err(ManifestError::UnterminatedFile("src/db.rs".to_string()))
+        );
+    }
+
+    #[test]
+    fn tests_fix_targets_files_named_in_errors() {
+        let mut files: ProjectFiles = ProjectFiles::default();
+        files
+            .insert("src/main.rs", "fn main() {}".to_string())
+            .unwrap();
+        files.insert("src/db.rs", "".to_string()).unwrap();
+        files.insert("src/routes.rs", "".to_string()).unwrap();
+
+        let errors: &str = "error[E0425]: cannot find value `x` in this scope\n  \
+            --> src/routes.rs:4:5\n\
+            error[E0308]: mismatched types\n  \
+            --> src/routes.rs:9:1\n\
+            warning: unused import\n  \
+            --> /home/user/.cargo/registry/src/lib.rs:1:1\n";
+        assert_eq!(
+            files.files_named_in(errors),
+            vec!["src/routes.rs".to_string()]
+        );
+        assert_eq!(
+            files.files_named_in("thread 'actix-rt' panicked at src/db.rs:12:5:\nboom"),
+            vec!["src/db.rs".to_string()]
+        );
+
+        let mut fixed: ProjectFiles = ProjectFiles::default();
+        fixed
+            .insert("src/routes.rs", "pub fn routes() {}".to_string())
+            .unwrap();
+        files.merge(fixed);
+        assert_eq!(
+            files.files.get("src/routes.rs").unwrap(),
+            "pub fn routes() {}"
+        );
+        assert_eq!(files.paths().len(), 3);
+    }
+}
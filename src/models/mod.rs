@@ -1,4 +1,4 @@
-pub mod general;
 pub mod agent_basic;
+pub mod agent_manager;
 pub mod agents;
-pub mod agent_manager;
\ No newline at end of file
+pub mod general;
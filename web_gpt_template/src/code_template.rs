@@ -143,6 +143,11 @@ async fn main() -> std::io::Result<()> {
 
     let data = web::Data::new(AppState { db: Mutex::new(db) });
 
+    let port: u16 = std::env::var("PORT")
+        .ok()
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(8080);
+
     HttpServer::new(move || {
         App::new()
             .wrap(
@@ -164,7 +169,7 @@ async fn main() -> std::io::Result<()> {
             .route("/register", web::post().to(register))
             .route("/login", web::post().to(login))
     })
-    .bind("127.0.0.1:8080")?
+    .bind(("127.0.0.1", port))?
     .run()
     .await
 }
\ No newline at end of file
@@ -109,6 +109,11 @@ async fn main() -> std::io::Result<()> {
 
     let data = web::Data::new(AppState { db: Mutex::new(db) });
 
+    let port: u16 = std::env::var("PORT")
+        .ok()
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(8080);
+
     HttpServer::new(move || {
         App::new()
             .wrap(
@@ -128,7 +133,7 @@ async fn main() -> std::io::Result<()> {
             .route("/forex_pair/{id}", web::delete().to(delete_forex_pair))
             .route("/forex_pair", web::get().to(read_all_forex_pairs))
     })
-    .bind("127.0.0.1:8080")?
+    .bind(("127.0.0.1", port))?
     .run()
     .await
 }
\ No newline at end of file